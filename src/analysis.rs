@@ -0,0 +1,214 @@
+//! Algorithms that answer questions about a finished [`CallGraph`](crate::call_graph::CallGraph).
+//!
+//! Like the renderers, this layer depends only on `CallGraph`: it never talks to the language
+//! server, so every analysis works the same on a freshly built graph and on one loaded with
+//! `--input`. Each submodule is a set of pure functions over the graph.
+
+pub mod baseline;
+pub mod cycles;
+pub mod dead_code;
+pub mod diff;
+pub mod impact;
+pub mod layering;
+pub mod modules;
+pub mod paths;
+pub mod sarif;
+pub mod stats;
+pub mod test_map;
+
+use crate::call_graph::{CallGraph, CallGraphNode};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Ids of the nodes that `name` refers to: nodes whose qualified label equals `name`, or,
+/// when there are none, nodes whose bare function name equals it.
+pub fn find_nodes_by_name<'g>(graph: &'g CallGraph, name: &str) -> Vec<&'g str> {
+    let exact: Vec<&str> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.label == name)
+        .map(|node| node.id.as_str())
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+    graph
+        .nodes
+        .iter()
+        .filter(|node| node.short_label() == name)
+        .map(|node| node.id.as_str())
+        .collect()
+}
+
+/// Ids of the nodes whose function path (the id without a `#n` suffix, see
+/// `call_graph::identity`) is `path` or ends with `::path`. A leading `crate::` in `path`
+/// stands for the crate name.
+pub fn find_nodes_by_path<'g>(graph: &'g CallGraph, path: &str) -> Vec<&'g str> {
+    let suffix = format!("::{}", path);
+    graph
+        .nodes
+        .iter()
+        .map(|node| node.id.as_str())
+        .filter(|id| {
+            let base = id.split_once('#').map_or(*id, |(base, _)| base);
+            match path.strip_prefix("crate::") {
+                Some(rest) => base.split_once("::").is_some_and(|(_, tail)| tail == rest),
+                None => base == path || base.ends_with(&suffix),
+            }
+        })
+        .collect()
+}
+
+/// Id of the innermost node whose definition spans `line` (1-based) of `file`, if any.
+pub fn find_node_at<'g>(graph: &'g CallGraph, file: &str, line: u32) -> Option<&'g str> {
+    graph
+        .nodes
+        .iter()
+        .filter_map(|node| Some((node, node.location.as_ref()?)))
+        .filter(|(_, l)| l.file == file && (l.start_line..=l.end_line).contains(&line))
+        .min_by_key(|(_, l)| l.end_line - l.start_line)
+        .map(|(node, _)| node.id.as_str())
+}
+
+/// Ids of every node reachable from `roots` by following calls, roots included.
+pub fn reachable_from<'g>(graph: &'g CallGraph, roots: &[&str]) -> HashSet<&'g str> {
+    let mut callees: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        callees.entry(&edge.from).or_default().push(&edge.to);
+    }
+    let mut seen: HashSet<&str> = graph
+        .nodes
+        .iter()
+        .map(|node| node.id.as_str())
+        .filter(|id| roots.contains(id))
+        .collect();
+    let mut stack: Vec<&str> = seen.iter().copied().collect();
+    while let Some(current) = stack.pop() {
+        for &next in callees.get(current).into_iter().flatten() {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Module path of the file defining `node`, derived from its workspace-relative path:
+/// `src/lsp/client.rs` and `src/lsp/client/mod.rs` give `lsp::client`, crate roots
+/// (`src/lib.rs`, `src/main.rs`) give `crate`, and files outside `src/` keep their path
+/// (`tests/cli.rs` gives `tests::cli`). Functions outside the workspace give their crate.
+/// `None` when the location is unknown.
+pub fn module_path(node: &CallGraphNode) -> Option<String> {
+    if node.external {
+        return Some(node.group.clone());
+    }
+    let file = &node.location.as_ref()?.file;
+    let without_ext = file.strip_suffix(".rs").unwrap_or(file);
+    let without_mod = without_ext.strip_suffix("/mod").unwrap_or(without_ext);
+    match without_mod.strip_prefix("src/") {
+        Some("lib" | "main") => Some(String::from("crate")),
+        Some(module) => Some(module.replace('/', "::")),
+        None => Some(without_mod.replace('/', "::")),
+    }
+}
+
+/// Pretty-printed JSON of a report, ending with a newline; shared by the `to_json` functions
+/// of the submodules.
+pub(crate) fn to_json<T: Serialize + ?Sized>(report: &T) -> String {
+    let mut out =
+        serde_json::to_string_pretty(report).expect("report contains only JSON-representable data");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location, node};
+
+    fn graph(labels: &[(&str, &str)]) -> CallGraph {
+        let nodes = labels
+            .iter()
+            .map(|(id, label)| node(id, label, ""))
+            .collect();
+        testing::graph(nodes, &[])
+    }
+
+    #[test]
+    fn find_nodes_by_name_prefers_qualified_match() {
+        let g = graph(&[("a", "A::new"), ("b", "B::new"), ("c", "new")]);
+        assert_eq!(find_nodes_by_name(&g, "A::new"), vec!["a"]);
+        assert_eq!(find_nodes_by_name(&g, "new"), vec!["c"]);
+    }
+
+    #[test]
+    fn find_nodes_by_name_falls_back_to_short_name() {
+        let g = graph(&[("a", "A::run"), ("b", "B::run")]);
+        assert_eq!(find_nodes_by_name(&g, "run"), vec!["a", "b"]);
+        assert!(find_nodes_by_name(&g, "missing").is_empty());
+    }
+
+    #[test]
+    fn find_nodes_by_path_matches_id_suffixes() {
+        let g = graph(&[
+            ("my_crate::lsp::LspClient::new", "LspClient::new"),
+            ("my_crate::point::Point::new#1", "Point::new"),
+            ("my_crate::point::Point::new#2", "Point::new"),
+        ]);
+        assert_eq!(
+            find_nodes_by_path(&g, "crate::lsp::LspClient::new"),
+            vec!["my_crate::lsp::LspClient::new"]
+        );
+        assert_eq!(
+            find_nodes_by_path(&g, "lsp::LspClient::new"),
+            vec!["my_crate::lsp::LspClient::new"]
+        );
+        assert_eq!(find_nodes_by_path(&g, "Point::new").len(), 2);
+        assert!(find_nodes_by_path(&g, "Client::new").is_empty());
+        assert!(find_nodes_by_path(&g, "crate::LspClient::new").is_empty());
+    }
+
+    #[test]
+    fn find_node_at_picks_innermost_definition() {
+        let at = |id: &str, start_line: u32, end_line: u32| CallGraphNode {
+            location: location("src/app.rs", start_line, end_line),
+            ..node(id, id, "")
+        };
+        let g = testing::graph(vec![at("outer", 10, 40), at("inner", 20, 25)], &[]);
+        assert_eq!(find_node_at(&g, "src/app.rs", 22), Some("inner"));
+        assert_eq!(find_node_at(&g, "src/app.rs", 30), Some("outer"));
+        assert_eq!(find_node_at(&g, "src/app.rs", 50), None);
+        assert_eq!(find_node_at(&g, "src/cli.rs", 22), None);
+    }
+
+    #[test]
+    fn reachable_from_follows_calls_transitively() {
+        let mut g = graph(&[("a", "a"), ("b", "b"), ("c", "c"), ("d", "d")]);
+        g.edges = [("a", "b"), ("b", "c"), ("d", "a")]
+            .iter()
+            .map(|&(from, to)| testing::edge(from, to))
+            .collect();
+        let reached = reachable_from(&g, &["a"]);
+        assert_eq!(reached, HashSet::from(["a", "b", "c"]));
+    }
+
+    #[test]
+    fn module_path_is_derived_from_file() {
+        let at = |file: &str| CallGraphNode {
+            location: location(file, 1, 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            module_path(&at("src/lsp/client.rs")).unwrap(),
+            "lsp::client"
+        );
+        assert_eq!(module_path(&at("src/lsp/mod.rs")).unwrap(), "lsp");
+        assert_eq!(module_path(&at("src/main.rs")).unwrap(), "crate");
+        assert_eq!(module_path(&at("tests/cli.rs")).unwrap(), "tests::cli");
+        assert_eq!(module_path(&CallGraphNode::default()), None);
+        let mut push = at("/home/dev/.rustup/lib/rustlib/src/rust/library/alloc/src/vec/mod.rs");
+        push.external = true;
+        push.group = "alloc".to_string();
+        assert_eq!(module_path(&push).unwrap(), "alloc");
+    }
+}
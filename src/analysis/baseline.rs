@@ -0,0 +1,250 @@
+//! Dependency freeze: calls that a committed baseline graph does not have.
+//!
+//! The baseline is a graph saved with the JSON output format. Calls are matched by the node
+//! ids of caller and callee, which are function paths (see `call_graph::identity`), so
+//! moving code around does not count as a new call. Calls the current graph no longer
+//! makes are fine; only new ones are reported.
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphNode, CallSite, Highlight};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Which new calls are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Every call.
+    All,
+    /// Calls between different groups (clusters), e.g. from one type or module into
+    /// another. Calls within a group stay free to change.
+    CrossGroup,
+}
+
+/// One call of the current graph that the baseline does not have.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewCall {
+    pub from: String,
+    pub to: String,
+    pub from_label: String,
+    pub to_label: String,
+    pub from_group: String,
+    pub to_group: String,
+    pub call_sites: Vec<CallSite>,
+}
+
+/// Every call in `current` that is not in `baseline` and lies in `scope`, in edge order.
+pub fn new_calls(baseline: &CallGraph, current: &CallGraph, scope: Scope) -> Vec<NewCall> {
+    let accepted: HashSet<(&str, &str)> = baseline
+        .edges
+        .iter()
+        .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+        .collect();
+    let nodes: HashMap<&str, &CallGraphNode> =
+        current.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+
+    current
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            if accepted.contains(&(edge.from.as_str(), edge.to.as_str())) {
+                return None;
+            }
+            let caller = nodes.get(edge.from.as_str())?;
+            let callee = nodes.get(edge.to.as_str())?;
+            if scope == Scope::CrossGroup && caller.group == callee.group {
+                return None;
+            }
+            Some(NewCall {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                from_label: caller.label.clone(),
+                to_label: callee.label.clone(),
+                from_group: caller.group.clone(),
+                to_group: callee.group.clone(),
+                call_sites: edge.call_sites.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Highlights every new call as [`Highlight::Added`].
+pub fn mark_new_calls(graph: &mut CallGraph, calls: &[NewCall]) {
+    let edges: HashSet<(&str, &str)> = calls
+        .iter()
+        .map(|c| (c.from.as_str(), c.to.as_str()))
+        .collect();
+    for edge in &mut graph.edges {
+        if edges.contains(&(edge.from.as_str(), edge.to.as_str())) {
+            edge.highlight = Some(Highlight::Added);
+        }
+    }
+}
+
+/// Text form of the new calls, one block per call.
+///
+/// ```text
+/// 1 call(s) not in the baseline
+///
+/// app -> LspClient
+///   run -> LspClient::new  at src/app.rs:12:5
+/// ```
+pub fn format_text(calls: &[NewCall]) -> String {
+    if calls.is_empty() {
+        return String::from("No calls beyond the baseline\n");
+    }
+    let mut out = format!("{} call(s) not in the baseline\n", calls.len());
+    for call in calls {
+        out.push_str(&format!("\n{} -> {}\n", call.from_group, call.to_group));
+        let line = format!("  {} -> {}", call.from_label, call.to_label);
+        if call.call_sites.is_empty() {
+            out.push_str(&format!("{}\n", line));
+        }
+        for site in &call.call_sites {
+            out.push_str(&format!(
+                "{}  at {}:{}:{}\n",
+                line, site.file, site.line, site.column
+            ));
+        }
+    }
+    out
+}
+
+/// JSON form of the new calls: `{"new_calls": [{"from", "to", "from_label", ...}]}`.
+pub fn to_json(calls: &[NewCall]) -> String {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        new_calls: &'a [NewCall],
+    }
+    analysis::to_json(&Document { new_calls: calls })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+
+    fn node(id: &str, label: &str, group: &str, line: u32) -> CallGraphNode {
+        CallGraphNode {
+            location: location("src/app.rs", line, line + 3),
+            ..testing::node(id, label, group)
+        }
+    }
+
+    /// Graph whose calls are all made at `src/app.rs:7:9`.
+    fn graph(nodes: Vec<CallGraphNode>, edges: &[(&str, &str)]) -> CallGraph {
+        let mut graph = testing::graph(nodes, edges);
+        for edge in &mut graph.edges {
+            edge.call_sites = vec![CallSite {
+                file: String::from("src/app.rs"),
+                line: 7,
+                column: 9,
+            }];
+        }
+        graph
+    }
+
+    /// App::run -> App::helper
+    fn baseline() -> CallGraph {
+        graph(
+            vec![
+                node("app::App::run", "App::run", "App", 10),
+                node("app::App::helper", "App::helper", "App", 30),
+                node("db::Db::query", "Db::query", "Db", 50),
+            ],
+            &[("app::App::run", "app::App::helper")],
+        )
+    }
+
+    /// Moved down; App::run -> App::parse (same group) and App::run -> Db::query (other group)
+    fn current() -> CallGraph {
+        graph(
+            vec![
+                node("app::App::run", "App::run", "App", 14),
+                node("app::App::helper", "App::helper", "App", 34),
+                node("db::Db::query", "Db::query", "Db", 54),
+                node("app::App::parse", "App::parse", "App", 60),
+            ],
+            &[
+                ("app::App::run", "app::App::helper"),
+                ("app::App::run", "db::Db::query"),
+                ("app::App::run", "app::App::parse"),
+            ],
+        )
+    }
+
+    fn pairs(calls: &[NewCall]) -> Vec<(&str, &str)> {
+        calls
+            .iter()
+            .map(|c| (c.from_label.as_str(), c.to_label.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn moved_calls_are_not_new() {
+        let calls = new_calls(&baseline(), &current(), Scope::All);
+        assert_eq!(
+            pairs(&calls),
+            vec![("App::run", "Db::query"), ("App::run", "App::parse")]
+        );
+        assert_eq!(calls[0].from, "app::App::run");
+        assert!(new_calls(&current(), &current(), Scope::All).is_empty());
+    }
+
+    #[test]
+    fn reordered_same_named_functions_keep_their_calls() {
+        let display = "point::<Point as Display>::fmt";
+        let debug = "point::<Point as Debug>::fmt";
+        let baseline = graph(
+            vec![
+                node("app::App::run", "App::run", "App", 10),
+                node(display, "Point::fmt", "Point", 20),
+                node(debug, "Point::fmt", "Point", 40),
+            ],
+            &[("app::App::run", display)],
+        );
+        // The Debug impl moved above the Display impl.
+        let current = graph(
+            vec![
+                node("app::App::run", "App::run", "App", 10),
+                node(display, "Point::fmt", "Point", 40),
+                node(debug, "Point::fmt", "Point", 20),
+            ],
+            &[("app::App::run", display)],
+        );
+        assert!(new_calls(&baseline, &current, Scope::All).is_empty());
+    }
+
+    #[test]
+    fn cross_group_scope_ignores_calls_within_a_group() {
+        let calls = new_calls(&baseline(), &current(), Scope::CrossGroup);
+        assert_eq!(pairs(&calls), vec![("App::run", "Db::query")]);
+    }
+
+    #[test]
+    fn report_lists_call_sites_and_marks_edges() {
+        let mut graph = current();
+        let calls = new_calls(&baseline(), &graph, Scope::CrossGroup);
+        assert_eq!(
+            format_text(&calls),
+            "1 call(s) not in the baseline\n\nApp -> Db\n  App::run -> Db::query  at src/app.rs:7:9\n"
+        );
+        assert_eq!(format_text(&[]), "No calls beyond the baseline\n");
+
+        mark_new_calls(&mut graph, &calls);
+        let marked: Vec<(&str, &str)> = graph
+            .edges
+            .iter()
+            .filter(|e| e.highlight == Some(Highlight::Added))
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(marked, vec![("app::App::run", "db::Db::query")]);
+    }
+
+    #[test]
+    fn json_lists_new_calls() {
+        let calls = new_calls(&baseline(), &current(), Scope::CrossGroup);
+        let value: serde_json::Value = serde_json::from_str(&to_json(&calls)).unwrap();
+        assert_eq!(value["new_calls"][0]["to_group"], "Db");
+        assert_eq!(value["new_calls"][0]["call_sites"][0]["line"], 7);
+    }
+}
@@ -0,0 +1,398 @@
+//! Recursion detection via strongly connected components.
+//!
+//! Every strongly connected component with more than one function is a mutual-recursion
+//! cycle; a single function that calls itself is direct recursion. [`find_cycles`] computes
+//! both with Tarjan's algorithm (iteratively, so deep graphs cannot overflow the stack),
+//! [`mark_cycles`] highlights them for the renderers, and [`format_cycle_report`] lists them
+//! with qualified labels and call-site lines, as text or JSON ([`to_json`]).
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode, CallSite, Highlight};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// One recursion cycle: a strongly connected component of the call graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// Ids of the functions in the cycle, in graph order.
+    pub nodes: Vec<String>,
+    /// Calls between functions of the cycle, as `(from, to)` ids in graph order.
+    pub edges: Vec<(String, String)>,
+}
+
+impl Cycle {
+    /// `true` for a single function calling itself.
+    pub fn is_direct_recursion(&self) -> bool {
+        self.nodes.len() == 1
+    }
+}
+
+/// All recursion cycles in `graph`, ordered by the position of their first node.
+pub fn find_cycles(graph: &CallGraph) -> Vec<Cycle> {
+    let index_of: HashMap<&str, usize> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect();
+    let mut callees: Vec<Vec<usize>> = vec![Vec::new(); graph.nodes.len()];
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (
+            index_of.get(edge.from.as_str()),
+            index_of.get(edge.to.as_str()),
+        ) {
+            callees[from].push(to);
+        }
+    }
+
+    let component_of = strongly_connected_components(&callees);
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (node, &component) in component_of.iter().enumerate() {
+        members.entry(component).or_default().push(node);
+    }
+    let self_calls: HashSet<usize> = (0..callees.len())
+        .filter(|&node| callees[node].contains(&node))
+        .collect();
+
+    let mut cycles: Vec<Cycle> = members
+        .into_values()
+        .filter(|nodes| nodes.len() > 1 || self_calls.contains(&nodes[0]))
+        .map(|mut nodes| {
+            nodes.sort_unstable();
+            let component = component_of[nodes[0]];
+            let edges = graph
+                .edges
+                .iter()
+                .filter(|edge| {
+                    let in_cycle =
+                        |id: &str| index_of.get(id).map(|&i| component_of[i]) == Some(component);
+                    in_cycle(&edge.from) && in_cycle(&edge.to)
+                })
+                .map(|edge| (edge.from.clone(), edge.to.clone()))
+                .collect();
+            Cycle {
+                nodes: nodes.iter().map(|&i| graph.nodes[i].id.clone()).collect(),
+                edges,
+            }
+        })
+        .collect();
+    cycles.sort_by_key(|cycle| index_of[cycle.nodes[0].as_str()]);
+    cycles
+}
+
+/// Tarjan's algorithm over an adjacency list. Returns the component number of each node.
+fn strongly_connected_components(callees: &[Vec<usize>]) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+    let count = callees.len();
+    let mut index = vec![UNVISITED; count];
+    let mut low_link = vec![0; count];
+    let mut on_stack = vec![false; count];
+    let mut component = vec![UNVISITED; count];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut next_component = 0;
+
+    for start in 0..count {
+        if index[start] != UNVISITED {
+            continue;
+        }
+        // Each frame is (node, position of the next callee to look at).
+        let mut frames = vec![(start, 0)];
+        index[start] = next_index;
+        low_link[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some(&mut (node, ref mut position)) = frames.last_mut() {
+            if let Some(&callee) = callees[node].get(*position) {
+                *position += 1;
+                if index[callee] == UNVISITED {
+                    index[callee] = next_index;
+                    low_link[callee] = next_index;
+                    next_index += 1;
+                    stack.push(callee);
+                    on_stack[callee] = true;
+                    frames.push((callee, 0));
+                } else if on_stack[callee] {
+                    low_link[node] = low_link[node].min(index[callee]);
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(parent, _)) = frames.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if low_link[node] == index[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component[member] = next_component;
+                    if member == node {
+                        break;
+                    }
+                }
+                next_component += 1;
+            }
+        }
+    }
+    component
+}
+
+/// Highlights the nodes and edges of `cycles` as [`Highlight::Cycle`].
+pub fn mark_cycles(graph: &mut CallGraph, cycles: &[Cycle]) {
+    let nodes: HashSet<&str> = cycles
+        .iter()
+        .flat_map(|c| &c.nodes)
+        .map(String::as_str)
+        .collect();
+    let edges: HashSet<(&str, &str)> = cycles
+        .iter()
+        .flat_map(|c| &c.edges)
+        .map(|(from, to)| (from.as_str(), to.as_str()))
+        .collect();
+    for node in &mut graph.nodes {
+        if nodes.contains(node.id.as_str()) {
+            node.highlight = Some(Highlight::Cycle);
+        }
+    }
+    for edge in &mut graph.edges {
+        if edges.contains(&(edge.from.as_str(), edge.to.as_str())) {
+            edge.highlight = Some(Highlight::Cycle);
+        }
+    }
+}
+
+/// Lists each cycle with its functions and the calls that close it.
+///
+/// ```text
+/// 1 cycle(s) found
+///
+/// Cycle 1: mutual recursion (2 functions)
+///   is_even  src/num.rs:1
+///   is_odd  src/num.rs:5
+///   calls:
+///     is_even -> is_odd  at src/num.rs:2:5
+///     is_odd -> is_even  at src/num.rs:6:5
+/// ```
+pub fn format_cycle_report(graph: &CallGraph, cycles: &[Cycle]) -> String {
+    if cycles.is_empty() {
+        return String::from("No recursion cycles found\n");
+    }
+    let nodes: HashMap<&str, &CallGraphNode> =
+        graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let edges: HashMap<(&str, &str), &CallGraphEdge> = graph
+        .edges
+        .iter()
+        .map(|e| ((e.from.as_str(), e.to.as_str()), e))
+        .collect();
+    let label = |id: &str| nodes.get(id).map_or(id.to_string(), |n| n.label.clone());
+
+    let mut out = format!("{} cycle(s) found\n", cycles.len());
+    for (index, cycle) in cycles.iter().enumerate() {
+        out.push('\n');
+        if cycle.is_direct_recursion() {
+            out.push_str(&format!("Cycle {}: direct recursion\n", index + 1));
+        } else {
+            out.push_str(&format!(
+                "Cycle {}: mutual recursion ({} functions)\n",
+                index + 1,
+                cycle.nodes.len()
+            ));
+        }
+        for id in &cycle.nodes {
+            out.push_str(&format!("  {}", label(id)));
+            if let Some(location) = nodes.get(id.as_str()).and_then(|n| n.location.as_ref()) {
+                out.push_str(&format!("  {}:{}", location.file, location.start_line));
+            }
+            out.push('\n');
+        }
+        out.push_str("  calls:\n");
+        for (from, to) in &cycle.edges {
+            let call = format!("    {} -> {}", label(from), label(to));
+            let sites = edges
+                .get(&(from.as_str(), to.as_str()))
+                .map(|edge| edge.call_sites.as_slice())
+                .unwrap_or_default();
+            if sites.is_empty() {
+                out.push_str(&format!("{}\n", call));
+            }
+            for site in sites {
+                out.push_str(&format!(
+                    "{}  at {}:{}:{}\n",
+                    call, site.file, site.line, site.column
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// JSON form of the cycle report:
+/// `{"cycles": [{"kind": "direct" | "mutual", "functions": [...], "calls": [...]}]}`.
+pub fn to_json(graph: &CallGraph, cycles: &[Cycle]) -> String {
+    #[derive(Serialize)]
+    struct Function<'a> {
+        id: &'a str,
+        label: &'a str,
+        file: Option<&'a str>,
+        line: Option<u32>,
+    }
+    #[derive(Serialize)]
+    struct Call<'a> {
+        from: &'a str,
+        to: &'a str,
+        call_sites: &'a [CallSite],
+    }
+    #[derive(Serialize)]
+    struct CycleOut<'a> {
+        kind: &'static str,
+        functions: Vec<Function<'a>>,
+        calls: Vec<Call<'a>>,
+    }
+    #[derive(Serialize)]
+    struct Document<'a> {
+        cycles: Vec<CycleOut<'a>>,
+    }
+
+    let nodes: HashMap<&str, &CallGraphNode> =
+        graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let edges: HashMap<(&str, &str), &CallGraphEdge> = graph
+        .edges
+        .iter()
+        .map(|e| ((e.from.as_str(), e.to.as_str()), e))
+        .collect();
+    let document = Document {
+        cycles: cycles
+            .iter()
+            .map(|cycle| CycleOut {
+                kind: if cycle.is_direct_recursion() {
+                    "direct"
+                } else {
+                    "mutual"
+                },
+                functions: cycle
+                    .nodes
+                    .iter()
+                    .map(|id| {
+                        let node = nodes.get(id.as_str());
+                        let location = node.and_then(|n| n.location.as_ref());
+                        Function {
+                            id,
+                            label: node.map_or(id, |n| &n.label),
+                            file: location.map(|l| l.file.as_str()),
+                            line: location.map(|l| l.start_line),
+                        }
+                    })
+                    .collect(),
+                calls: cycle
+                    .edges
+                    .iter()
+                    .map(|(from, to)| Call {
+                        from,
+                        to,
+                        call_sites: edges
+                            .get(&(from.as_str(), to.as_str()))
+                            .map(|edge| edge.call_sites.as_slice())
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    analysis::to_json(&document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+    use crate::call_graph::CallSite;
+
+    fn make_graph(ids: &[&str], edges: &[(&str, &str)]) -> CallGraph {
+        let nodes = ids.iter().map(|id| testing::node(id, id, "")).collect();
+        testing::graph(nodes, edges)
+    }
+
+    #[test]
+    fn acyclic_graph_has_no_cycles() {
+        let graph = make_graph(&["a", "b", "c"], &[("a", "b"), ("b", "c"), ("a", "c")]);
+        assert!(find_cycles(&graph).is_empty());
+    }
+
+    #[test]
+    fn self_call_is_direct_recursion() {
+        let graph = make_graph(&["main", "walk"], &[("main", "walk"), ("walk", "walk")]);
+        let cycles = find_cycles(&graph);
+        assert_eq!(cycles.len(), 1);
+        assert!(cycles[0].is_direct_recursion());
+        assert_eq!(
+            cycles[0].edges,
+            vec![("walk".to_string(), "walk".to_string())]
+        );
+    }
+
+    #[test]
+    fn mutual_recursion_forms_one_cycle_with_internal_edges() {
+        let graph = make_graph(
+            &["main", "even", "odd", "leaf"],
+            &[
+                ("main", "even"),
+                ("even", "odd"),
+                ("odd", "even"),
+                ("odd", "leaf"),
+            ],
+        );
+        let cycles = find_cycles(&graph);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].nodes, vec!["even", "odd"]);
+        assert_eq!(cycles[0].edges.len(), 2);
+    }
+
+    #[test]
+    fn separate_cycles_are_reported_separately() {
+        let graph = make_graph(
+            &["a", "b", "c", "d"],
+            &[("a", "b"), ("b", "a"), ("b", "c"), ("c", "d"), ("d", "c")],
+        );
+        let cycles = find_cycles(&graph);
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0].nodes, vec!["a", "b"]);
+        assert_eq!(cycles[1].nodes, vec!["c", "d"]);
+    }
+
+    #[test]
+    fn mark_cycles_highlights_only_cycle_edges() {
+        let mut graph = make_graph(&["a", "b", "c"], &[("a", "b"), ("b", "a"), ("b", "c")]);
+        let cycles = find_cycles(&graph);
+        mark_cycles(&mut graph, &cycles);
+        assert_eq!(graph.nodes[0].highlight, Some(Highlight::Cycle));
+        assert_eq!(graph.nodes[2].highlight, None);
+        assert_eq!(graph.edges[0].highlight, Some(Highlight::Cycle));
+        assert_eq!(graph.edges[2].highlight, None);
+    }
+
+    #[test]
+    fn report_lists_labels_locations_and_call_sites() {
+        let mut graph = make_graph(&["f"], &[("f", "f")]);
+        graph.nodes[0].label = "Tree::walk".to_string();
+        graph.nodes[0].location = location("src/tree.rs", 10, 20);
+        graph.edges[0].call_sites = vec![CallSite {
+            file: "src/tree.rs".to_string(),
+            line: 14,
+            column: 9,
+        }];
+        let report = format_cycle_report(&graph, &find_cycles(&graph));
+        assert_eq!(
+            report,
+            "1 cycle(s) found\n\nCycle 1: direct recursion\n  Tree::walk  src/tree.rs:10\n  calls:\n    Tree::walk -> Tree::walk  at src/tree.rs:14:9\n"
+        );
+
+        let value: serde_json::Value =
+            serde_json::from_str(&to_json(&graph, &find_cycles(&graph))).unwrap();
+        assert_eq!(value["cycles"][0]["kind"], "direct");
+        assert_eq!(value["cycles"][0]["functions"][0]["label"], "Tree::walk");
+        assert_eq!(value["cycles"][0]["calls"][0]["call_sites"][0]["line"], 14);
+    }
+}
@@ -0,0 +1,303 @@
+//! Functions that no chosen entry point can reach.
+//!
+//! Works on a graph that contains every workspace function (the all-symbols graph), so that
+//! functions nobody calls are present as nodes. [`select_roots`] picks the entry points
+//! described by a [`RootSelection`]; [`find_unreached`] groups every function outside their
+//! reach by module. Test code is never reported: it is not meant to be reachable from
+//! production entry points. Neither are functions outside the workspace, which are never
+//! roots either. Methods of trait impls count as reached: rust-analyzer reports calls to the
+//! trait method declaration, and many are only called from other crates (`fmt`, `default`).
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphNode, Highlight};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// Which functions count as entry points.
+#[derive(Debug, Clone, Default)]
+pub struct RootSelection {
+    /// Every function named `main`.
+    pub main: bool,
+    /// Every function whose signature starts with `pub ` (public API of a library).
+    /// `pub(crate)` and narrower visibilities do not count.
+    pub public: bool,
+    /// Every function in test code (`#[test]` functions and their helpers).
+    pub tests: bool,
+    /// Node ids of functions chosen explicitly.
+    pub ids: Vec<String>,
+}
+
+/// Ids of the nodes selected as roots.
+pub fn select_roots<'g>(graph: &'g CallGraph, selection: &RootSelection) -> Vec<&'g str> {
+    let named: HashSet<&str> = selection.ids.iter().map(String::as_str).collect();
+    graph
+        .nodes
+        .iter()
+        .filter(|node| {
+            named.contains(node.id.as_str())
+                || (!node.external
+                    && ((selection.main && node.label == "main")
+                        || (selection.public && is_public(node))
+                        || (selection.tests && node.is_test)))
+        })
+        .map(|node| node.id.as_str())
+        .collect()
+}
+
+fn is_public(node: &CallGraphNode) -> bool {
+    node.detail
+        .as_deref()
+        .is_some_and(|detail| detail.starts_with("pub "))
+}
+
+/// `true` for the id of a method in a trait impl, whose path ends with
+/// `::<Type as Trait>::name` (see `call_graph::identity`).
+fn is_trait_impl_method(id: &str) -> bool {
+    let path = id.split_once('#').map_or(id, |(path, _)| path);
+    path.rsplit_once("::").is_some_and(|(owner, _)| {
+        owner.ends_with('>')
+            && owner
+                .rfind("::<")
+                .is_some_and(|start| owner[start..].contains(" as "))
+    })
+}
+
+/// An unreached function as it appears in the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadFunction {
+    pub id: String,
+    pub label: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// Result of a dead-code analysis.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadCodeReport {
+    /// Qualified labels of the roots used.
+    pub roots: Vec<String>,
+    /// Unreached functions keyed by module path (see [`analysis::module_path`]), each list
+    /// in graph order.
+    pub modules: BTreeMap<String, Vec<DeadFunction>>,
+}
+
+impl DeadCodeReport {
+    pub fn unreached_count(&self) -> usize {
+        self.modules.values().map(Vec::len).sum()
+    }
+
+    /// Ids of every unreached function.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.modules.values().flatten().map(|f| f.id.as_str())
+    }
+}
+
+/// Lists the non-test functions of `graph` that none of `roots`, nor any trait impl method,
+/// reaches.
+pub fn find_unreached(graph: &CallGraph, roots: &[&str]) -> DeadCodeReport {
+    let trait_impl_methods = graph
+        .nodes
+        .iter()
+        .filter(|node| !node.external && is_trait_impl_method(&node.id))
+        .map(|node| node.id.as_str());
+    let starts: Vec<&str> = roots.iter().copied().chain(trait_impl_methods).collect();
+    let reached = analysis::reachable_from(graph, &starts);
+    let mut modules: BTreeMap<String, Vec<DeadFunction>> = BTreeMap::new();
+    for node in &graph.nodes {
+        if node.is_test || node.external || reached.contains(node.id.as_str()) {
+            continue;
+        }
+        let module = analysis::module_path(node).unwrap_or_else(|| String::from("(unknown)"));
+        modules.entry(module).or_default().push(DeadFunction {
+            id: node.id.clone(),
+            label: node.label.clone(),
+            file: node.location.as_ref().map(|l| l.file.clone()),
+            line: node.location.as_ref().map(|l| l.start_line),
+        });
+    }
+
+    let roots: HashSet<&str> = roots.iter().copied().collect();
+    DeadCodeReport {
+        roots: graph
+            .nodes
+            .iter()
+            .filter(|node| roots.contains(node.id.as_str()))
+            .map(|node| node.label.clone())
+            .collect(),
+        modules,
+    }
+}
+
+/// Highlights every function listed in `report` as [`Highlight::Unreached`].
+pub fn mark_unreached(graph: &mut CallGraph, report: &DeadCodeReport) {
+    let ids: HashSet<&str> = report.ids().collect();
+    for node in &mut graph.nodes {
+        if ids.contains(node.id.as_str()) {
+            node.highlight = Some(Highlight::Unreached);
+        }
+    }
+}
+
+/// Text form of the report, one section per module.
+///
+/// ```text
+/// 2 unreached function(s) from 1 root(s)
+///
+/// lsp::client
+///   LspClient::old_request  src/lsp/client.rs:120
+///   debug_dump  src/lsp/client.rs:300
+/// ```
+pub fn format_text(report: &DeadCodeReport) -> String {
+    let mut out = format!(
+        "{} unreached function(s) from {} root(s)\n",
+        report.unreached_count(),
+        report.roots.len()
+    );
+    for (module, functions) in &report.modules {
+        out.push_str(&format!("\n{}\n", module));
+        for function in functions {
+            out.push_str(&format!("  {}", function.label));
+            if let (Some(file), Some(line)) = (&function.file, function.line) {
+                out.push_str(&format!("  {}:{}", file, line));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// JSON form of the report:
+/// `{"roots": [...], "unreached_count": N, "modules": {"path": [{"id", "label", "file", "line"}]}}`.
+pub fn to_json(report: &DeadCodeReport) -> String {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        #[serde(flatten)]
+        report: &'a DeadCodeReport,
+        unreached_count: usize,
+    }
+    analysis::to_json(&Document {
+        report,
+        unreached_count: report.unreached_count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+
+    fn node(id: &str, label: &str, file: &str, detail: &str) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, 10, 12),
+            detail: Some(detail.to_string()),
+            ..testing::node(id, label, "")
+        }
+    }
+
+    /// main -> run -> helper; unused and Api::get are never called; test_run -> run
+    fn sample_graph() -> CallGraph {
+        let mut test_fn = node("t", "test_run", "src/app.rs", "fn test_run()");
+        test_fn.is_test = true;
+        testing::graph(
+            vec![
+                node("m", "main", "src/main.rs", "fn main()"),
+                node("r", "run", "src/app.rs", "pub(crate) fn run()"),
+                node("h", "helper", "src/app.rs", "fn helper()"),
+                node("u", "unused", "src/util.rs", "fn unused()"),
+                node("g", "Api::get", "src/api.rs", "pub fn get(&self)"),
+                test_fn,
+            ],
+            &[("m", "r"), ("r", "h"), ("t", "r")],
+        )
+    }
+
+    fn unreached_labels(report: &DeadCodeReport) -> Vec<&str> {
+        report
+            .modules
+            .values()
+            .flatten()
+            .map(|f| f.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn select_roots_combines_kinds_and_names() {
+        let graph = sample_graph();
+        let main_only = RootSelection {
+            main: true,
+            ..Default::default()
+        };
+        assert_eq!(select_roots(&graph, &main_only), vec!["m"]);
+
+        let all = RootSelection {
+            main: true,
+            public: true,
+            tests: true,
+            ids: vec!["u".to_string()],
+        };
+        assert_eq!(select_roots(&graph, &all), vec!["m", "u", "g", "t"]);
+    }
+
+    #[test]
+    fn unreached_functions_are_grouped_by_module() {
+        let graph = sample_graph();
+        let report = find_unreached(&graph, &["m"]);
+        assert_eq!(report.roots, vec!["main"]);
+        assert_eq!(
+            report.modules.keys().collect::<Vec<_>>(),
+            vec!["api", "util"]
+        );
+        assert_eq!(unreached_labels(&report), vec!["Api::get", "unused"]);
+    }
+
+    #[test]
+    fn test_code_is_never_reported() {
+        let graph = sample_graph();
+        let report = find_unreached(&graph, &[]);
+        assert!(!unreached_labels(&report).contains(&"test_run"));
+        assert_eq!(report.unreached_count(), 5);
+    }
+
+    #[test]
+    fn trait_impl_methods_and_their_callees_are_reached() {
+        let fmt = "my_crate::point::<Point as fmt::Display>::fmt";
+        let mut graph = sample_graph();
+        graph
+            .nodes
+            .push(node(fmt, "Point::fmt", "src/point.rs", "fn fmt(&self)"));
+        graph.nodes.push(node(
+            "my_crate::wrap::Wrapper<T>::get",
+            "Wrapper::get",
+            "src/wrap.rs",
+            "fn get(&self)",
+        ));
+        graph.edges.push(testing::edge(fmt, "u"));
+        let report = find_unreached(&graph, &["m"]);
+        assert_eq!(report.roots, vec!["main"]);
+        assert_eq!(unreached_labels(&report), vec!["Api::get", "Wrapper::get"]);
+        assert!(is_trait_impl_method("c::m::<Vec<T> as Default>::default#2"));
+        assert!(!is_trait_impl_method("c::m::Point::fmt"));
+    }
+
+    #[test]
+    fn mark_unreached_highlights_listed_nodes() {
+        let mut graph = sample_graph();
+        let report = find_unreached(&graph, &["m"]);
+        mark_unreached(&mut graph, &report);
+        assert_eq!(graph.nodes[3].highlight, Some(Highlight::Unreached));
+        assert_eq!(graph.nodes[0].highlight, None);
+    }
+
+    #[test]
+    fn text_and_json_forms_list_locations() {
+        let graph = sample_graph();
+        let report = find_unreached(&graph, &["m"]);
+        assert_eq!(
+            format_text(&report),
+            "2 unreached function(s) from 1 root(s)\n\napi\n  Api::get  src/api.rs:10\n\nutil\n  unused  src/util.rs:10\n"
+        );
+        let value: serde_json::Value = serde_json::from_str(&to_json(&report)).unwrap();
+        assert_eq!(value["unreached_count"], 2);
+        assert_eq!(value["modules"]["util"][0]["line"], 10);
+    }
+}
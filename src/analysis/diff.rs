@@ -0,0 +1,340 @@
+//! Differences between two snapshots of a call graph.
+//!
+//! Nodes are matched by id. Graphs built from a workspace use the function's path as id
+//! (see `call_graph::identity`), e.g. `my_crate::lsp::client::LspClient::send`, so moving a
+//! function within its file is not a change. [`diff`] lists added and removed functions
+//! and calls; [`combined_graph`] merges both snapshots into one graph with the changes
+//! highlighted.
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode, Highlight};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// What changed from one snapshot to the next. Functions and calls are given by node id;
+/// lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    pub added_functions: Vec<String>,
+    pub removed_functions: Vec<String>,
+    pub added_calls: Vec<(String, String)>,
+    pub removed_calls: Vec<(String, String)>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_functions.is_empty()
+            && self.removed_functions.is_empty()
+            && self.added_calls.is_empty()
+            && self.removed_calls.is_empty()
+    }
+}
+
+fn functions(graph: &CallGraph) -> HashSet<&str> {
+    graph.nodes.iter().map(|node| node.id.as_str()).collect()
+}
+
+/// Calls of `graph` as `(caller, callee)` ids.
+fn calls(graph: &CallGraph) -> HashSet<(&str, &str)> {
+    graph
+        .edges
+        .iter()
+        .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+        .collect()
+}
+
+/// Changes from `old` to `new`.
+pub fn diff(old: &CallGraph, new: &CallGraph) -> GraphDiff {
+    let (old_functions, new_functions) = (functions(old), functions(new));
+    let (old_calls, new_calls) = (calls(old), calls(new));
+    let owned = |(from, to): &(&str, &str)| (from.to_string(), to.to_string());
+
+    fn sorted<T: Ord + Clone>(items: impl Iterator<Item = T>) -> Vec<T> {
+        let mut items: Vec<T> = items.collect();
+        items.sort();
+        items
+    }
+    GraphDiff {
+        added_functions: sorted(
+            new_functions
+                .difference(&old_functions)
+                .map(|f| f.to_string()),
+        ),
+        removed_functions: sorted(
+            old_functions
+                .difference(&new_functions)
+                .map(|f| f.to_string()),
+        ),
+        added_calls: sorted(new_calls.difference(&old_calls).map(owned)),
+        removed_calls: sorted(old_calls.difference(&new_calls).map(owned)),
+    }
+}
+
+/// Functions and calls of one kind of change, by id.
+struct ChangeSet<'d> {
+    functions: HashSet<&'d str>,
+    calls: HashSet<(&'d str, &'d str)>,
+}
+
+impl<'d> ChangeSet<'d> {
+    fn new(functions: &'d [String], calls: &'d [(String, String)]) -> Self {
+        ChangeSet {
+            functions: functions.iter().map(String::as_str).collect(),
+            calls: calls
+                .iter()
+                .map(|(from, to)| (from.as_str(), to.as_str()))
+                .collect(),
+        }
+    }
+}
+
+/// `new` plus the functions and calls only `old` has. Additions are highlighted as
+/// [`Highlight::Added`], removals as [`Highlight::Removed`]; every other highlight is cleared.
+pub fn combined_graph(old: &CallGraph, new: &CallGraph, changes: &GraphDiff) -> CallGraph {
+    let added = ChangeSet::new(&changes.added_functions, &changes.added_calls);
+    let removed = ChangeSet::new(&changes.removed_functions, &changes.removed_calls);
+    let mut combined = CallGraph {
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    append_snapshot(&mut combined, new, &added, Highlight::Added, false);
+    append_snapshot(&mut combined, old, &removed, Highlight::Removed, true);
+    combined
+}
+
+/// Appends the nodes and edges of `graph` to `combined`, marking the ones in `changed` with
+/// `highlight`. With `only_changed`, everything else is skipped.
+fn append_snapshot(
+    combined: &mut CallGraph,
+    graph: &CallGraph,
+    changed: &ChangeSet<'_>,
+    highlight: Highlight,
+    only_changed: bool,
+) {
+    for node in &graph.nodes {
+        let is_changed = changed.functions.contains(node.id.as_str());
+        if only_changed && !is_changed {
+            continue;
+        }
+        combined.nodes.push(CallGraphNode {
+            highlight: is_changed.then_some(highlight),
+            ..node.clone()
+        });
+    }
+    for edge in &graph.edges {
+        let is_changed = changed
+            .calls
+            .contains(&(edge.from.as_str(), edge.to.as_str()));
+        if only_changed && !is_changed {
+            continue;
+        }
+        combined.edges.push(CallGraphEdge {
+            highlight: is_changed.then_some(highlight),
+            ..edge.clone()
+        });
+    }
+}
+
+/// Text form of the changes.
+///
+/// ```text
+/// +1 -0 function(s), +2 -1 call(s)
+///
+/// Added functions:
+///   + app::parse_args
+///
+/// Added calls:
+///   + crate::main -> app::parse_args
+/// ...
+/// ```
+pub fn format_text(changes: &GraphDiff) -> String {
+    if changes.is_empty() {
+        return String::from("No changes in the call graph\n");
+    }
+    let mut out = format!(
+        "+{} -{} function(s), +{} -{} call(s)\n",
+        changes.added_functions.len(),
+        changes.removed_functions.len(),
+        changes.added_calls.len(),
+        changes.removed_calls.len()
+    );
+    let sections = [
+        ("Added functions", '+', &changes.added_functions),
+        ("Removed functions", '-', &changes.removed_functions),
+    ];
+    for (title, sign, functions) in sections {
+        if !functions.is_empty() {
+            out.push_str(&format!("\n{}:\n", title));
+            for function in functions {
+                out.push_str(&format!("  {} {}\n", sign, function));
+            }
+        }
+    }
+    let sections = [
+        ("Added calls", '+', &changes.added_calls),
+        ("Removed calls", '-', &changes.removed_calls),
+    ];
+    for (title, sign, calls) in sections {
+        if !calls.is_empty() {
+            out.push_str(&format!("\n{}:\n", title));
+            for (from, to) in calls {
+                out.push_str(&format!("  {} {} -> {}\n", sign, from, to));
+            }
+        }
+    }
+    out
+}
+
+/// JSON form of the changes: `{"added_functions": [...], "removed_functions": [...],
+/// "added_calls": [{"from", "to"}], "removed_calls": [...]}`.
+pub fn to_json(changes: &GraphDiff) -> String {
+    #[derive(Serialize)]
+    struct Call<'a> {
+        from: &'a str,
+        to: &'a str,
+    }
+    #[derive(Serialize)]
+    struct Document<'a> {
+        added_functions: &'a [String],
+        removed_functions: &'a [String],
+        added_calls: Vec<Call<'a>>,
+        removed_calls: Vec<Call<'a>>,
+    }
+    fn calls(calls: &[(String, String)]) -> Vec<Call<'_>> {
+        calls.iter().map(|(from, to)| Call { from, to }).collect()
+    }
+    let document = Document {
+        added_functions: &changes.added_functions,
+        removed_functions: &changes.removed_functions,
+        added_calls: calls(&changes.added_calls),
+        removed_calls: calls(&changes.removed_calls),
+    };
+    analysis::to_json(&document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, graph, location};
+
+    fn node(id: &str, label: &str, file: &str, line: u32) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, line, line + 3),
+            ..testing::node(id, label, "")
+        }
+    }
+
+    /// main -> run -> helper
+    fn old_graph() -> CallGraph {
+        graph(
+            vec![
+                node("crate::main", "main", "src/main.rs", 1),
+                node("app::App::run", "App::run", "src/app.rs", 10),
+                node("app::helper", "helper", "src/app.rs", 30),
+            ],
+            &[
+                ("crate::main", "app::App::run"),
+                ("app::App::run", "app::helper"),
+            ],
+        )
+    }
+
+    /// Same functions moved down a few lines, helper replaced by parse: main -> run -> parse
+    fn new_graph() -> CallGraph {
+        graph(
+            vec![
+                node("crate::main", "main", "src/main.rs", 3),
+                node("app::App::run", "App::run", "src/app.rs", 14),
+                node("app::parse", "parse", "src/app.rs", 40),
+            ],
+            &[
+                ("crate::main", "app::App::run"),
+                ("app::App::run", "app::parse"),
+            ],
+        )
+    }
+
+    #[test]
+    fn reordered_same_named_functions_are_not_changes() {
+        let display = "point::<Point as Display>::fmt";
+        let debug = "point::<Point as Debug>::fmt";
+        let old = graph(
+            vec![
+                node(display, "Point::fmt", "src/point.rs", 20),
+                node(debug, "Point::fmt", "src/point.rs", 50),
+                node("crate::main", "main", "src/main.rs", 1),
+            ],
+            &[("crate::main", display)],
+        );
+        // The Debug impl moved above the Display impl.
+        let new = graph(
+            vec![
+                node(debug, "Point::fmt", "src/point.rs", 20),
+                node(display, "Point::fmt", "src/point.rs", 50),
+                node("crate::main", "main", "src/main.rs", 1),
+            ],
+            &[("crate::main", display)],
+        );
+        assert!(diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn diff_matches_moved_functions_by_identity() {
+        let changes = diff(&old_graph(), &new_graph());
+        assert_eq!(changes.added_functions, vec!["app::parse"]);
+        assert_eq!(changes.removed_functions, vec!["app::helper"]);
+        assert_eq!(
+            changes.added_calls,
+            vec![("app::App::run".to_string(), "app::parse".to_string())]
+        );
+        assert_eq!(
+            changes.removed_calls,
+            vec![("app::App::run".to_string(), "app::helper".to_string())]
+        );
+        assert!(diff(&old_graph(), &old_graph()).is_empty());
+    }
+
+    #[test]
+    fn combined_graph_highlights_additions_and_removals() {
+        let (old, new) = (old_graph(), new_graph());
+        let combined = combined_graph(&old, &new, &diff(&old, &new));
+        let nodes: Vec<(&str, Option<Highlight>)> = combined
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n.highlight))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("crate::main", None),
+                ("app::App::run", None),
+                ("app::parse", Some(Highlight::Added)),
+                ("app::helper", Some(Highlight::Removed)),
+            ]
+        );
+        let edges: Vec<(&str, Option<Highlight>)> = combined
+            .edges
+            .iter()
+            .map(|e| (e.to.as_str(), e.highlight))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("app::App::run", None),
+                ("app::parse", Some(Highlight::Added)),
+                ("app::helper", Some(Highlight::Removed)),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_and_json_forms_list_changes() {
+        let changes = diff(&old_graph(), &new_graph());
+        assert_eq!(
+            format_text(&changes),
+            "+1 -1 function(s), +1 -1 call(s)\n\nAdded functions:\n  + app::parse\n\nRemoved functions:\n  - app::helper\n\nAdded calls:\n  + app::App::run -> app::parse\n\nRemoved calls:\n  - app::App::run -> app::helper\n"
+        );
+        let value: serde_json::Value = serde_json::from_str(&to_json(&changes)).unwrap();
+        assert_eq!(value["removed_calls"][0]["to"], "app::helper");
+    }
+}
@@ -0,0 +1,215 @@
+//! Change impact: what a change to some functions can affect.
+//!
+//! Works on a caller graph whose roots are the changed functions, as built from a diff: every
+//! other node calls a changed function, directly or not. [`analyze`] lists the changed
+//! functions, their callers, the entry points among them (functions nobody in the graph
+//! calls) and the test functions, which are the tests worth running for the change.
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphNode, Highlight};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// A function as it appears in the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImpactedFunction {
+    pub id: String,
+    pub label: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl ImpactedFunction {
+    fn of(node: &CallGraphNode) -> Self {
+        ImpactedFunction {
+            id: node.id.clone(),
+            label: node.label.clone(),
+            file: node.location.as_ref().map(|l| l.file.clone()),
+            line: node.location.as_ref().map(|l| l.start_line),
+        }
+    }
+}
+
+/// Result of a change-impact analysis. Every list is sorted by label.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImpactReport {
+    /// Functions the change touches (the roots of the graph).
+    pub changed: Vec<ImpactedFunction>,
+    /// Functions that transitively call a changed function.
+    pub callers: Vec<ImpactedFunction>,
+    /// Changed functions and callers that nothing in the graph calls.
+    pub entry_points: Vec<ImpactedFunction>,
+    /// Changed functions and callers in test code.
+    pub tests: Vec<ImpactedFunction>,
+}
+
+/// Sorts the impact of the roots of `graph` into the report lists.
+pub fn analyze(graph: &CallGraph) -> ImpactReport {
+    let called: HashSet<&str> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.from != edge.to)
+        .map(|edge| edge.to.as_str())
+        .collect();
+    let mut report = ImpactReport::default();
+    for node in &graph.nodes {
+        let function = ImpactedFunction::of(node);
+        if !called.contains(node.id.as_str()) {
+            report.entry_points.push(function.clone());
+        }
+        if node.is_test {
+            report.tests.push(function.clone());
+        }
+        if node.is_root {
+            report.changed.push(function);
+        } else {
+            report.callers.push(function);
+        }
+    }
+    for list in [
+        &mut report.changed,
+        &mut report.callers,
+        &mut report.entry_points,
+        &mut report.tests,
+    ] {
+        list.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+    }
+    report
+}
+
+/// Highlights the changed functions as [`Highlight::Changed`].
+pub fn mark_changed(graph: &mut CallGraph) {
+    for node in &mut graph.nodes {
+        if node.is_root {
+            node.highlight = Some(Highlight::Changed);
+        }
+    }
+}
+
+/// Text form of the report, one section per list.
+///
+/// ```text
+/// 1 changed function(s), 2 caller(s), 1 entry point(s), 1 test(s)
+///
+/// Changed:
+///   parse_args  src/cli.rs:40
+///
+/// Callers:
+///   main  src/main.rs:3
+///   parses_flags  tests/cli.rs:8
+/// ...
+/// ```
+pub fn format_text(report: &ImpactReport) -> String {
+    if report.changed.is_empty() {
+        return String::from("The change touches no function\n");
+    }
+    let mut out = format!(
+        "{} changed function(s), {} caller(s), {} entry point(s), {} test(s)\n",
+        report.changed.len(),
+        report.callers.len(),
+        report.entry_points.len(),
+        report.tests.len()
+    );
+    let sections = [
+        ("Changed", &report.changed),
+        ("Callers", &report.callers),
+        ("Entry points", &report.entry_points),
+        ("Tests", &report.tests),
+    ];
+    for (title, functions) in sections {
+        if functions.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{}:\n", title));
+        for function in functions {
+            out.push_str(&format!("  {}", function.label));
+            if let (Some(file), Some(line)) = (&function.file, function.line) {
+                out.push_str(&format!("  {}:{}", file, line));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// JSON form of the report: `{"changed": [{"id", "label", "file", "line"}], "callers": [...],
+/// "entry_points": [...], "tests": [...]}`.
+pub fn to_json(report: &ImpactReport) -> String {
+    analysis::to_json(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+
+    fn node(id: &str, file: &str, line: u32) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, line, line + 5),
+            is_test: file.starts_with("tests/"),
+            ..testing::node(id, id, "")
+        }
+    }
+
+    /// main -> run -> parse_args (changed) <- parses_flags (test); parse_args calls itself
+    fn impact_graph() -> CallGraph {
+        let mut changed = node("parse_args", "src/cli.rs", 40);
+        changed.is_root = true;
+        testing::graph(
+            vec![
+                changed,
+                node("run", "src/app.rs", 10),
+                node("main", "src/main.rs", 3),
+                node("parses_flags", "tests/cli.rs", 8),
+            ],
+            &[
+                ("main", "run"),
+                ("run", "parse_args"),
+                ("parses_flags", "parse_args"),
+                ("parse_args", "parse_args"),
+            ],
+        )
+    }
+
+    fn labels(functions: &[ImpactedFunction]) -> Vec<&str> {
+        functions.iter().map(|f| f.label.as_str()).collect()
+    }
+
+    #[test]
+    fn report_sorts_functions_into_lists() {
+        let report = analyze(&impact_graph());
+        assert_eq!(labels(&report.changed), vec!["parse_args"]);
+        assert_eq!(labels(&report.callers), vec!["main", "parses_flags", "run"]);
+        assert_eq!(labels(&report.entry_points), vec!["main", "parses_flags"]);
+        assert_eq!(labels(&report.tests), vec!["parses_flags"]);
+    }
+
+    #[test]
+    fn uncalled_changed_function_is_its_own_entry_point() {
+        let mut graph = impact_graph();
+        graph.edges.retain(|edge| edge.from == edge.to);
+        let report = analyze(&graph);
+        assert!(labels(&report.entry_points).contains(&"parse_args"));
+    }
+
+    #[test]
+    fn text_lists_sections_and_locations() {
+        let text = format_text(&analyze(&impact_graph()));
+        assert!(text.starts_with(
+            "1 changed function(s), 3 caller(s), 2 entry point(s), 1 test(s)\n\nChanged:\n  parse_args  src/cli.rs:40\n"
+        ));
+        assert!(text.contains("\nTests:\n  parses_flags  tests/cli.rs:8\n"));
+        assert_eq!(
+            format_text(&ImpactReport::default()),
+            "The change touches no function\n"
+        );
+    }
+
+    #[test]
+    fn changed_functions_are_highlighted() {
+        let mut graph = impact_graph();
+        mark_changed(&mut graph);
+        assert_eq!(graph.nodes[0].highlight, Some(Highlight::Changed));
+        assert!(graph.nodes[1..].iter().all(|n| n.highlight.is_none()));
+    }
+}
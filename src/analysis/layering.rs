@@ -0,0 +1,401 @@
+//! Architecture layering rules checked against the calls of a [`CallGraph`].
+//!
+//! Rules are written in TOML and refer to modules by path (see [`analysis::module_path`]).
+//! A module pattern matches that module and everything below it: `lsp` matches `lsp` and
+//! `lsp::client`; `*` matches every module.
+//!
+//! ```toml
+//! # Layers from top to bottom. A module may call its own layer and the layers below it.
+//! [[layers]]
+//! name = "app"
+//! modules = ["crate", "app"]
+//!
+//! [[layers]]
+//! name = "lsp"
+//! modules = ["lsp"]
+//!
+//! # Calls that are never allowed, whatever the layers say.
+//! [[forbid]]
+//! from = "dot_renderer"
+//! to = "lsp"
+//! reason = "renderers do not know about LSP"
+//!
+//! # Exceptions: matching calls are never reported.
+//! [[allow]]
+//! from = "lsp_session"
+//! to = "cli"
+//! ```
+//!
+//! Calls within one module, and calls whose endpoints have no known location, are never
+//! checked.
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphNode, CallSite, Highlight};
+use crate::error::RulesError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A parsed rules file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayerRules {
+    /// Layers from top to bottom.
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+    /// Calls reported regardless of layers.
+    #[serde(default)]
+    pub forbid: Vec<DirectionRule>,
+    /// Calls never reported.
+    #[serde(default)]
+    pub allow: Vec<DirectionRule>,
+}
+
+/// A named set of module patterns.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Layer {
+    pub name: String,
+    pub modules: Vec<String>,
+}
+
+/// Calls from modules matching `from` to modules matching `to`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DirectionRule {
+    pub from: String,
+    pub to: String,
+    /// Shown next to violations of a `forbid` rule.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl DirectionRule {
+    fn matches(&self, from: &str, to: &str) -> bool {
+        pattern_matches(&self.from, from) && pattern_matches(&self.to, to)
+    }
+}
+
+impl LayerRules {
+    /// Parses a rules file. Empty patterns and a pattern listed in two layers are rejected.
+    pub fn from_toml(text: &str) -> Result<Self, RulesError> {
+        let rules: LayerRules = toml::from_str(text).map_err(|e| RulesError::Parse {
+            reason: e.message().to_string(),
+        })?;
+        let mut seen = HashSet::new();
+        let patterns = rules.layers.iter().flat_map(|layer| &layer.modules);
+        let rule_patterns = rules
+            .forbid
+            .iter()
+            .chain(&rules.allow)
+            .flat_map(|rule| [&rule.from, &rule.to]);
+        if patterns.clone().chain(rule_patterns).any(|p| p.is_empty()) {
+            return Err(RulesError::EmptyPattern);
+        }
+        for pattern in patterns {
+            if !seen.insert(pattern.as_str()) {
+                return Err(RulesError::DuplicateLayerModule {
+                    module: pattern.clone(),
+                });
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Index of the layer `module` belongs to, by its most specific matching pattern.
+    fn layer_of(&self, module: &str) -> Option<usize> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(index, layer)| layer.modules.iter().map(move |p| (index, p)))
+            .filter(|(_, pattern)| pattern_matches(pattern, module))
+            .max_by_key(|(_, pattern)| pattern_specificity(pattern))
+            .map(|(index, _)| index)
+    }
+
+    /// Why a call from module `from` to module `to` breaks the rules, if it does.
+    fn violation(&self, from: &str, to: &str) -> Option<String> {
+        if self.allow.iter().any(|rule| rule.matches(from, to)) {
+            return None;
+        }
+        if let Some(rule) = self.forbid.iter().find(|rule| rule.matches(from, to)) {
+            let mut rule_text = format!("forbidden: {} -> {}", rule.from, rule.to);
+            if let Some(reason) = &rule.reason {
+                rule_text.push_str(&format!(" ({})", reason));
+            }
+            return Some(rule_text);
+        }
+        match (self.layer_of(from), self.layer_of(to)) {
+            (Some(caller), Some(callee)) if callee < caller => Some(format!(
+                "layer '{}' calls higher layer '{}'",
+                self.layers[caller].name, self.layers[callee].name
+            )),
+            _ => None,
+        }
+    }
+}
+
+fn pattern_matches(pattern: &str, module: &str) -> bool {
+    pattern == "*"
+        || module == pattern
+        || module
+            .strip_prefix(pattern)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// `*` is the least specific pattern; otherwise deeper paths are more specific.
+fn pattern_specificity(pattern: &str) -> usize {
+    if pattern == "*" {
+        0
+    } else {
+        pattern.split("::").count()
+    }
+}
+
+/// One call that breaks a rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// The rule broken, in words.
+    pub rule: String,
+    pub from: String,
+    pub to: String,
+    pub from_label: String,
+    pub to_label: String,
+    pub from_module: String,
+    pub to_module: String,
+    pub call_sites: Vec<CallSite>,
+}
+
+/// Every call in `graph` that breaks `rules`, in edge order.
+pub fn check(graph: &CallGraph, rules: &LayerRules) -> Vec<Violation> {
+    let nodes: HashMap<&str, &CallGraphNode> =
+        graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let module = |id: &str| {
+        let node = nodes.get(id)?;
+        node.location.as_ref()?;
+        Some((*node, analysis::module_path(node)?))
+    };
+    graph
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            let (caller, from_module) = module(&edge.from)?;
+            let (callee, to_module) = module(&edge.to)?;
+            if from_module == to_module {
+                return None;
+            }
+            let rule = rules.violation(&from_module, &to_module)?;
+            Some(Violation {
+                rule,
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                from_label: caller.label.clone(),
+                to_label: callee.label.clone(),
+                from_module,
+                to_module,
+                call_sites: edge.call_sites.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Highlights every violating edge as [`Highlight::Violation`].
+pub fn mark_violations(graph: &mut CallGraph, violations: &[Violation]) {
+    let edges: HashSet<(&str, &str)> = violations
+        .iter()
+        .map(|v| (v.from.as_str(), v.to.as_str()))
+        .collect();
+    for edge in &mut graph.edges {
+        if edges.contains(&(edge.from.as_str(), edge.to.as_str())) {
+            edge.highlight = Some(Highlight::Violation);
+        }
+    }
+}
+
+/// Text form of the violations, one block per call.
+///
+/// ```text
+/// 1 layering violation(s)
+///
+/// dot_renderer -> lsp: forbidden: dot_renderer -> lsp (renderers do not know about LSP)
+///   to_dot -> LspClient::new  at src/dot_renderer.rs:12:5
+/// ```
+pub fn format_text(violations: &[Violation]) -> String {
+    if violations.is_empty() {
+        return String::from("No layering violations found\n");
+    }
+    let mut out = format!("{} layering violation(s)\n", violations.len());
+    for violation in violations {
+        out.push_str(&format!(
+            "\n{} -> {}: {}\n",
+            violation.from_module, violation.to_module, violation.rule
+        ));
+        let call = format!("  {} -> {}", violation.from_label, violation.to_label);
+        if violation.call_sites.is_empty() {
+            out.push_str(&format!("{}\n", call));
+        }
+        for site in &violation.call_sites {
+            out.push_str(&format!(
+                "{}  at {}:{}:{}\n",
+                call, site.file, site.line, site.column
+            ));
+        }
+    }
+    out
+}
+
+/// JSON form of the violations: `{"violations": [{"rule", "from", "to", "from_label", ...}]}`.
+pub fn to_json(violations: &[Violation]) -> String {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        violations: &'a [Violation],
+    }
+    analysis::to_json(&Document { violations })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+
+    const RULES: &str = r#"
+[[layers]]
+name = "app"
+modules = ["crate", "app"]
+
+[[layers]]
+name = "domain"
+modules = ["call_graph_builder", "dot_renderer"]
+
+[[layers]]
+name = "lsp"
+modules = ["lsp"]
+
+[[forbid]]
+from = "dot_renderer"
+to = "lsp"
+reason = "renderers do not know about LSP"
+
+[[allow]]
+from = "lsp::session"
+to = "app"
+"#;
+
+    fn node(id: &str, file: &str) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, 1, 5),
+            ..testing::node(id, id, "")
+        }
+    }
+
+    fn graph(edges: &[(&str, &str)]) -> CallGraph {
+        let nodes = vec![
+            node("run", "src/app.rs"),
+            node("build", "src/call_graph_builder.rs"),
+            node("to_dot", "src/dot_renderer.rs"),
+            node("request", "src/lsp/client.rs"),
+            node("start", "src/lsp/session.rs"),
+            node("helper", "src/util.rs"),
+        ];
+        let mut graph = testing::graph(nodes, edges);
+        for edge in &mut graph.edges {
+            edge.call_sites = vec![CallSite {
+                file: String::from("src/x.rs"),
+                line: 3,
+                column: 9,
+            }];
+        }
+        graph
+    }
+
+    fn rules() -> LayerRules {
+        LayerRules::from_toml(RULES).unwrap()
+    }
+
+    #[test]
+    fn downward_and_unlayered_calls_are_allowed() {
+        let graph = graph(&[
+            ("run", "build"),
+            ("build", "request"),
+            ("request", "helper"),
+            ("helper", "run"),
+        ]);
+        assert!(check(&graph, &rules()).is_empty());
+    }
+
+    #[test]
+    fn upward_calls_break_layers() {
+        let graph = graph(&[("request", "build")]);
+        let violations = check(&graph, &rules());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].rule,
+            "layer 'lsp' calls higher layer 'domain'"
+        );
+        assert_eq!(violations[0].from_module, "lsp::client");
+    }
+
+    #[test]
+    fn forbid_applies_within_layers_and_allow_overrides() {
+        let graph = graph(&[("to_dot", "request"), ("start", "run")]);
+        let violations = check(&graph, &rules());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].rule,
+            "forbidden: dot_renderer -> lsp (renderers do not know about LSP)"
+        );
+    }
+
+    #[test]
+    fn most_specific_pattern_decides_the_layer() {
+        let rules = LayerRules::from_toml(
+            "[[layers]]\nname = \"top\"\nmodules = [\"lsp::session\"]\n\
+             [[layers]]\nname = \"bottom\"\nmodules = [\"*\"]\n",
+        )
+        .unwrap();
+        assert!(check(&graph(&[("start", "request")]), &rules).is_empty());
+        assert_eq!(check(&graph(&[("request", "start")]), &rules).len(), 1);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let duplicate = "[[layers]]\nname = \"a\"\nmodules = [\"x\"]\n\
+                         [[layers]]\nname = \"b\"\nmodules = [\"x\"]\n";
+        assert!(matches!(
+            LayerRules::from_toml(duplicate),
+            Err(RulesError::DuplicateLayerModule { .. })
+        ));
+        assert!(matches!(
+            LayerRules::from_toml("[[forbid]]\nfrom = \"\"\nto = \"x\"\n"),
+            Err(RulesError::EmptyPattern)
+        ));
+        assert!(matches!(
+            LayerRules::from_toml("[[layer]]\nname = \"typo\"\n"),
+            Err(RulesError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn report_lists_call_sites_and_marks_edges() {
+        let mut graph = graph(&[("to_dot", "request"), ("run", "build")]);
+        let violations = check(&graph, &rules());
+        assert_eq!(
+            format_text(&violations),
+            "1 layering violation(s)\n\ndot_renderer -> lsp::client: forbidden: dot_renderer -> lsp (renderers do not know about LSP)\n  to_dot -> request  at src/x.rs:3:9\n"
+        );
+        let value: serde_json::Value = serde_json::from_str(&to_json(&violations)).unwrap();
+        assert_eq!(value["violations"][0]["call_sites"][0]["line"], 3);
+
+        mark_violations(&mut graph, &violations);
+        assert_eq!(graph.edges[0].highlight, Some(Highlight::Violation));
+        assert_eq!(graph.edges[1].highlight, None);
+    }
+
+    #[test]
+    fn repository_rules_file_parses() {
+        let rules =
+            LayerRules::from_toml(include_str!("../../docs/dev/architecture-rules.toml")).unwrap();
+        assert_eq!(rules.layers[0].name, "entry");
+        assert!(rules.violation("dot_renderer", "lsp::lsp_client").is_some());
+        assert!(rules.violation("app", "lsp").is_none());
+    }
+}
@@ -0,0 +1,189 @@
+//! Module-level dependency graph: "module A calls module B" instead of function calls.
+//!
+//! [`collapse`] turns a function graph into a graph with one node per module (or group) and
+//! one edge per pair of modules with calls between them. The result is an ordinary
+//! [`CallGraph`], so every renderer and analysis applies to it unchanged.
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode, CallSite};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What a function node is collapsed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collapse {
+    /// Its [`group`](CallGraphNode::group) (the cluster in the rendered output).
+    Group,
+    /// Its module path (see [`analysis::module_path`]), cut to the first `depth` segments
+    /// when given: depth 1 turns `lsp::client` into `lsp`.
+    ModulePath { depth: Option<usize> },
+}
+
+impl Collapse {
+    /// Name of the module `node` collapses into.
+    pub fn key(&self, node: &CallGraphNode) -> String {
+        match self {
+            Collapse::Group => node.group.clone(),
+            Collapse::ModulePath { depth } => {
+                let path = analysis::module_path(node).unwrap_or_else(|| String::from("(unknown)"));
+                let Some(depth) = depth else {
+                    return path;
+                };
+                let segments: Vec<&str> = path.split("::").take(*depth).collect();
+                segments.join("::")
+            }
+        }
+    }
+}
+
+/// Collapses `graph` into a module graph.
+///
+/// Each module node is labelled with the module name and grouped under its parent module
+/// (everything before the last `::`), so the renderers nest `lsp::client` inside an `lsp`
+/// cluster. It is a root when any of its functions is, and test code only when all of them
+/// are. Each edge carries the call sites of every underlying call, so its call count is
+/// the number of calls from one module into the other. Calls within a module are dropped.
+/// An edge is a dispatch edge when every underlying edge is.
+pub fn collapse(graph: &CallGraph, by: Collapse) -> CallGraph {
+    let mut modules: BTreeMap<String, CallGraphNode> = BTreeMap::new();
+    let mut module_of: BTreeMap<&str, String> = BTreeMap::new();
+    for node in &graph.nodes {
+        let key = by.key(node);
+        let module = modules.entry(key.clone()).or_insert_with(|| CallGraphNode {
+            id: key.clone(),
+            label: key.clone(),
+            group: key
+                .rsplit_once("::")
+                .map(|(parent, _)| parent.to_string())
+                .unwrap_or_default(),
+            is_test: true,
+            ..Default::default()
+        });
+        module.is_root |= node.is_root;
+        module.is_test &= node.is_test;
+        module_of.insert(node.id.as_str(), key);
+    }
+
+    let mut calls: BTreeMap<(&str, &str), BTreeSet<CallSite>> = BTreeMap::new();
+    let mut written: BTreeSet<(&str, &str)> = BTreeSet::new();
+    for edge in &graph.edges {
+        let (Some(from), Some(to)) = (
+            module_of.get(edge.from.as_str()),
+            module_of.get(edge.to.as_str()),
+        ) else {
+            continue;
+        };
+        if from != to {
+            calls
+                .entry((from, to))
+                .or_default()
+                .extend(edge.call_sites.iter().cloned());
+            if !edge.dispatch {
+                written.insert((from, to));
+            }
+        }
+    }
+
+    CallGraph {
+        nodes: modules.into_values().collect(),
+        edges: calls
+            .into_iter()
+            .map(|((from, to), sites)| CallGraphEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                call_sites: sites.into_iter().collect(),
+                highlight: None,
+                dispatch: !written.contains(&(from, to)),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+
+    fn node(id: &str, group: &str, file: &str) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, 1, 2),
+            ..testing::node(id, id, group)
+        }
+    }
+
+    fn site(line: u32) -> CallSite {
+        CallSite {
+            file: String::from("src/main.rs"),
+            line,
+            column: 5,
+        }
+    }
+
+    fn edge(from: &str, to: &str, lines: &[u32]) -> CallGraphEdge {
+        CallGraphEdge {
+            call_sites: lines.iter().map(|&line| site(line)).collect(),
+            ..testing::edge(from, to)
+        }
+    }
+
+    /// main (crate) -> connect, send (lsp::client) -> encode (lsp::codec); connect -> send
+    fn sample_graph() -> CallGraph {
+        let mut main = node("main", "main", "src/main.rs");
+        main.is_root = true;
+        CallGraph {
+            nodes: vec![
+                main,
+                node("connect", "LspClient", "src/lsp/client.rs"),
+                node("send", "LspClient", "src/lsp/client.rs"),
+                node("encode", "codec", "src/lsp/codec.rs"),
+            ],
+            edges: vec![
+                edge("main", "connect", &[3]),
+                edge("main", "send", &[4, 5]),
+                edge("connect", "send", &[10]),
+                edge("send", "encode", &[20]),
+            ],
+        }
+    }
+
+    fn edge_counts(graph: &CallGraph) -> Vec<(&str, &str, usize)> {
+        graph
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.call_count()))
+            .collect()
+    }
+
+    #[test]
+    fn module_path_collapse_counts_calls_between_modules() {
+        let modules = collapse(&sample_graph(), Collapse::ModulePath { depth: None });
+        let ids: Vec<&str> = modules.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["crate", "lsp::client", "lsp::codec"]);
+        assert_eq!(
+            edge_counts(&modules),
+            vec![
+                ("crate", "lsp::client", 3),
+                ("lsp::client", "lsp::codec", 1)
+            ]
+        );
+        assert_eq!(modules.nodes[1].group, "lsp");
+        assert!(modules.nodes[0].is_root);
+        assert!(!modules.nodes[1].is_root);
+    }
+
+    #[test]
+    fn depth_merges_submodules_and_drops_internal_calls() {
+        let modules = collapse(&sample_graph(), Collapse::ModulePath { depth: Some(1) });
+        let ids: Vec<&str> = modules.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["crate", "lsp"]);
+        assert_eq!(edge_counts(&modules), vec![("crate", "lsp", 3)]);
+    }
+
+    #[test]
+    fn group_collapse_uses_node_groups() {
+        let modules = collapse(&sample_graph(), Collapse::Group);
+        assert_eq!(
+            edge_counts(&modules),
+            vec![("LspClient", "codec", 1), ("main", "LspClient", 3)]
+        );
+    }
+}
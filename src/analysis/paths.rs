@@ -0,0 +1,323 @@
+//! Call paths between two sets of functions.
+//!
+//! A path is the list of node ids from a source to a target, following edges from caller to
+//! callee. [`shortest_path`] finds one path with the fewest calls; [`simple_paths`] lists
+//! paths that visit no node twice, up to a limit. [`path_subgraph`] cuts the graph down to
+//! the nodes and edges on the found paths so it can be rendered like any other graph, and
+//! [`format_paths`] prints them as call chains.
+
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A path as node ids, source first.
+pub type CallPath = Vec<String>;
+
+fn callee_map(graph: &CallGraph) -> HashMap<&str, Vec<&str>> {
+    let mut callees: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        callees.entry(&edge.from).or_default().push(&edge.to);
+    }
+    callees
+}
+
+/// One path with the fewest calls from any of `sources` to any of `targets`.
+///
+/// Ties are broken by edge order, so the result is deterministic for a given graph.
+pub fn shortest_path(graph: &CallGraph, sources: &[&str], targets: &[&str]) -> Option<CallPath> {
+    let callees = callee_map(graph);
+    let targets: HashSet<&str> = targets.iter().copied().collect();
+    let mut previous: HashMap<&str, Option<&str>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for &source in sources {
+        if previous.insert(source, None).is_none() {
+            queue.push_back(source);
+        }
+    }
+
+    while let Some(current) = queue.pop_front() {
+        if targets.contains(current) {
+            let mut path = vec![current.to_string()];
+            let mut cursor = current;
+            while let Some(Some(prev)) = previous.get(cursor) {
+                path.push(prev.to_string());
+                cursor = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &next in callees.get(current).into_iter().flatten() {
+            if !previous.contains_key(next) {
+                previous.insert(next, Some(current));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Up to `limit` simple paths from any of `sources` to any of `targets`, in depth-first
+/// order. A path ends at the first target it reaches.
+pub fn simple_paths(
+    graph: &CallGraph,
+    sources: &[&str],
+    targets: &[&str],
+    limit: usize,
+) -> Vec<CallPath> {
+    let callees = callee_map(graph);
+    let targets: HashSet<&str> = targets.iter().copied().collect();
+    let useful = reaching(graph, &targets);
+
+    let mut paths = Vec::new();
+    for &source in sources {
+        if useful.contains(source) {
+            extend_paths(&callees, &targets, &useful, source, limit, &mut paths);
+        }
+    }
+    paths
+}
+
+/// Adds the simple paths from `source` to `paths` until it holds `limit` of them. Walks
+/// depth-first with an explicit stack, like `cycles`, so long call chains cannot overflow
+/// the call stack.
+fn extend_paths<'g>(
+    callees: &HashMap<&'g str, Vec<&'g str>>,
+    targets: &HashSet<&str>,
+    useful: &HashSet<&str>,
+    source: &'g str,
+    limit: usize,
+    paths: &mut Vec<CallPath>,
+) {
+    if paths.len() >= limit {
+        return;
+    }
+    if targets.contains(source) {
+        paths.push(vec![source.to_string()]);
+        return;
+    }
+    // The current path, each node with the index of the next of its callees to try.
+    let mut stack: Vec<(&str, usize)> = vec![(source, 0)];
+    let mut on_path: HashSet<&str> = HashSet::from([source]);
+    while paths.len() < limit {
+        let Some((current, next_index)) = stack.last_mut() else {
+            break;
+        };
+        let current = *current;
+        let next = callees
+            .get(current)
+            .and_then(|c| c.get(*next_index))
+            .copied();
+        *next_index += 1;
+        match next {
+            None => {
+                on_path.remove(current);
+                stack.pop();
+            }
+            // Skip nodes already on the path (keeps it simple) and nodes that cannot lead to
+            // a target at all.
+            Some(next) if on_path.contains(next) || !useful.contains(next) => {}
+            Some(next) if targets.contains(next) => {
+                let mut path: CallPath = stack.iter().map(|(id, _)| id.to_string()).collect();
+                path.push(next.to_string());
+                paths.push(path);
+            }
+            Some(next) => {
+                on_path.insert(next);
+                stack.push((next, 0));
+            }
+        }
+    }
+}
+
+/// Nodes from which some target can be reached, targets included.
+fn reaching<'g>(graph: &'g CallGraph, targets: &HashSet<&str>) -> HashSet<&'g str> {
+    let mut callers: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        callers.entry(&edge.to).or_default().push(&edge.from);
+    }
+    let mut seen: HashSet<&str> = graph
+        .nodes
+        .iter()
+        .map(|node| node.id.as_str())
+        .filter(|id| targets.contains(id))
+        .collect();
+    let mut stack: Vec<&str> = seen.iter().copied().collect();
+    while let Some(current) = stack.pop() {
+        for &caller in callers.get(current).into_iter().flatten() {
+            if seen.insert(caller) {
+                stack.push(caller);
+            }
+        }
+    }
+    seen
+}
+
+/// The part of `graph` made of the nodes and consecutive-pair edges of `paths`.
+pub fn path_subgraph(graph: &CallGraph, paths: &[CallPath]) -> CallGraph {
+    let nodes_on_paths: HashSet<&str> = paths.iter().flatten().map(String::as_str).collect();
+    let edges_on_paths: HashSet<(&str, &str)> = paths
+        .iter()
+        .flat_map(|path| path.windows(2).map(|w| (w[0].as_str(), w[1].as_str())))
+        .collect();
+
+    let nodes: Vec<CallGraphNode> = graph
+        .nodes
+        .iter()
+        .filter(|node| nodes_on_paths.contains(node.id.as_str()))
+        .cloned()
+        .collect();
+    let edges: Vec<CallGraphEdge> = graph
+        .edges
+        .iter()
+        .filter(|edge| edges_on_paths.contains(&(edge.from.as_str(), edge.to.as_str())))
+        .cloned()
+        .collect();
+    CallGraph { nodes, edges }
+}
+
+/// Prints each path as a call chain: one function per line with its definition, followed by
+/// the call site in its caller.
+///
+/// ```text
+/// Path 1 of 1 (2 calls)
+///   main  src/main.rs:40
+///   App::run  src/app.rs:18  (called at src/main.rs:45:5)
+///   build_graph  src/app.rs:60  (called at src/app.rs:30:22)
+/// ```
+pub fn format_paths(graph: &CallGraph, paths: &[CallPath]) -> String {
+    let nodes: HashMap<&str, &CallGraphNode> =
+        graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let edges: HashMap<(&str, &str), &CallGraphEdge> = graph
+        .edges
+        .iter()
+        .map(|e| ((e.from.as_str(), e.to.as_str()), e))
+        .collect();
+
+    let mut out = String::new();
+    for (index, path) in paths.iter().enumerate() {
+        let calls = path.len().saturating_sub(1);
+        out.push_str(&format!(
+            "Path {} of {} ({} call{})\n",
+            index + 1,
+            paths.len(),
+            calls,
+            if calls == 1 { "" } else { "s" }
+        ));
+        for (position, id) in path.iter().enumerate() {
+            let mut line = format!(
+                "  {}",
+                nodes.get(id.as_str()).map_or(id.as_str(), |n| &n.label)
+            );
+            if let Some(location) = nodes.get(id.as_str()).and_then(|n| n.location.as_ref()) {
+                line.push_str(&format!("  {}:{}", location.file, location.start_line));
+            }
+            let call_site = position
+                .checked_sub(1)
+                .and_then(|p| edges.get(&(path[p].as_str(), id.as_str())))
+                .and_then(|edge| edge.call_sites.first());
+            if let Some(site) = call_site {
+                line.push_str(&format!(
+                    "  (called at {}:{}:{})",
+                    site.file, site.line, site.column
+                ));
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{graph, location, node};
+    use crate::call_graph::CallSite;
+
+    /// main -> a -> c -> target, main -> b -> target, c -> a (cycle), main -> other
+    fn sample_graph() -> CallGraph {
+        let ids = ["main", "a", "b", "c", "target", "other"];
+        graph(
+            ids.iter().map(|id| node(id, id, "")).collect(),
+            &[
+                ("main", "a"),
+                ("main", "b"),
+                ("main", "other"),
+                ("a", "c"),
+                ("c", "a"),
+                ("c", "target"),
+                ("b", "target"),
+            ],
+        )
+    }
+
+    #[test]
+    fn shortest_path_takes_fewest_calls() {
+        let path = shortest_path(&sample_graph(), &["main"], &["target"]).unwrap();
+        assert_eq!(path, vec!["main", "b", "target"]);
+    }
+
+    #[test]
+    fn shortest_path_is_none_when_unreachable() {
+        assert!(shortest_path(&sample_graph(), &["target"], &["main"]).is_none());
+    }
+
+    #[test]
+    fn simple_paths_lists_every_path_without_repeating_nodes() {
+        let paths = simple_paths(&sample_graph(), &["main"], &["target"], 10);
+        assert_eq!(
+            paths,
+            vec![
+                vec!["main", "a", "c", "target"],
+                vec!["main", "b", "target"]
+            ]
+        );
+    }
+
+    #[test]
+    fn simple_paths_stops_at_limit() {
+        let paths = simple_paths(&sample_graph(), &["main"], &["target"], 1);
+        assert_eq!(paths.len(), 1);
+    }
+
+    #[test]
+    fn simple_paths_follows_long_chains() {
+        let ids: Vec<String> = (0..20_000).map(|i| format!("f{}", i)).collect();
+        let edges: Vec<(&str, &str)> = ids
+            .windows(2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect();
+        let chain = graph(ids.iter().map(|id| node(id, id, "")).collect(), &edges);
+        let paths = simple_paths(&chain, &["f0"], &["f19999"], 10);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].len(), 20_000);
+        assert_eq!(simple_paths(&chain, &["f5"], &["f5"], 10), vec![vec!["f5"]]);
+    }
+
+    #[test]
+    fn path_subgraph_keeps_only_path_nodes_and_edges() {
+        let graph = sample_graph();
+        let paths = simple_paths(&graph, &["main"], &["target"], 10);
+        let sub = path_subgraph(&graph, &paths);
+        let ids: Vec<&str> = sub.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["main", "a", "b", "c", "target"]);
+        // c -> a lies between path nodes but is not on any path.
+        assert!(!sub.edges.iter().any(|e| e.from == "c" && e.to == "a"));
+        assert_eq!(sub.edges.len(), 5);
+    }
+
+    #[test]
+    fn format_paths_shows_locations_and_call_sites() {
+        let mut graph = sample_graph();
+        graph.nodes[2].location = location("src/b.rs", 7, 9);
+        graph.edges[1].call_sites = vec![CallSite {
+            file: "src/main.rs".to_string(),
+            line: 3,
+            column: 5,
+        }];
+        let paths = vec![vec!["main".to_string(), "b".to_string()]];
+        assert_eq!(
+            format_paths(&graph, &paths),
+            "Path 1 of 1 (1 call)\n  main\n  b  src/b.rs:7  (called at src/main.rs:3:5)\n"
+        );
+    }
+}
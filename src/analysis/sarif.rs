@@ -0,0 +1,497 @@
+//! SARIF 2.1.0 form of analysis findings, for code review tools that show them inline.
+//!
+//! Each analysis converts its results into [`Finding`]s (`from_cycles`, `from_dead_code`,
+//! `from_violations`, `from_new_calls`); [`to_sarif`] wraps them into a single-run SARIF
+//! log. Locations are the function definitions (the call hierarchy ranges recorded on the
+//! nodes) or, for calls, the call sites. Workspace-relative paths are given relative to
+//! `%SRCROOT%`.
+
+use crate::analysis;
+use crate::analysis::baseline::NewCall;
+use crate::analysis::cycles::Cycle;
+use crate::analysis::dead_code::DeadCodeReport;
+use crate::analysis::layering::Violation;
+use crate::call_graph::{CallGraph, CallGraphNode, CallSite};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The SARIF rules findings refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingRule {
+    RecursionCycle,
+    UnreachedFunction,
+    LayeringViolation,
+    NewCall,
+}
+
+impl FindingRule {
+    const ALL: [FindingRule; 4] = [
+        FindingRule::RecursionCycle,
+        FindingRule::UnreachedFunction,
+        FindingRule::LayeringViolation,
+        FindingRule::NewCall,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            FindingRule::RecursionCycle => "recursion-cycle",
+            FindingRule::UnreachedFunction => "unreached-function",
+            FindingRule::LayeringViolation => "layering-violation",
+            FindingRule::NewCall => "new-call",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            FindingRule::RecursionCycle => "Functions that call themselves, directly or not",
+            FindingRule::UnreachedFunction => "Function that no entry point reaches",
+            FindingRule::LayeringViolation => "Call that breaks an architecture layering rule",
+            FindingRule::NewCall => "Call that the dependency baseline does not allow",
+        }
+    }
+
+    /// SARIF `level`: broken rules fail the check, the other findings are advisory.
+    fn level(self) -> &'static str {
+        match self {
+            FindingRule::LayeringViolation | FindingRule::NewCall => "error",
+            FindingRule::RecursionCycle | FindingRule::UnreachedFunction => "warning",
+        }
+    }
+}
+
+/// A source region a finding points at. Lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FindingLocation {
+    pub file: String,
+    pub start_line: u32,
+    pub end_line: Option<u32>,
+    pub start_column: Option<u32>,
+}
+
+impl FindingLocation {
+    fn of_node(node: &CallGraphNode) -> Option<Self> {
+        let location = node.location.as_ref()?;
+        Some(FindingLocation {
+            file: location.file.clone(),
+            start_line: location.start_line,
+            end_line: Some(location.end_line),
+            start_column: None,
+        })
+    }
+
+    fn of_call_site(site: &CallSite) -> Self {
+        FindingLocation {
+            file: site.file.clone(),
+            start_line: site.line,
+            end_line: None,
+            start_column: Some(site.column),
+        }
+    }
+}
+
+/// One result of an analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: FindingRule,
+    pub message: String,
+    /// Where the finding is reported. Empty when no location is known.
+    pub location: Option<FindingLocation>,
+    /// Further places involved, e.g. the other functions of a cycle.
+    pub related: Vec<FindingLocation>,
+}
+
+/// One finding per cycle, located at its first function, with the other functions related.
+pub fn from_cycles(graph: &CallGraph, cycles: &[Cycle]) -> Vec<Finding> {
+    let nodes: HashMap<&str, &CallGraphNode> =
+        graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    cycles
+        .iter()
+        .map(|cycle| {
+            let members: Vec<&CallGraphNode> = cycle
+                .nodes
+                .iter()
+                .filter_map(|id| nodes.get(id.as_str()).copied())
+                .collect();
+            let labels: Vec<&str> = members.iter().map(|n| n.label.as_str()).collect();
+            let message = if cycle.is_direct_recursion() {
+                format!("Direct recursion: {} calls itself", labels.join(", "))
+            } else {
+                format!("Mutual recursion between {}", labels.join(", "))
+            };
+            let mut locations = members.iter().filter_map(|n| FindingLocation::of_node(n));
+            Finding {
+                rule: FindingRule::RecursionCycle,
+                message,
+                location: locations.next(),
+                related: locations.collect(),
+            }
+        })
+        .collect()
+}
+
+/// One finding per unreached function, located at its definition.
+pub fn from_dead_code(graph: &CallGraph, report: &DeadCodeReport) -> Vec<Finding> {
+    let nodes: HashMap<&str, &CallGraphNode> =
+        graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    report
+        .modules
+        .values()
+        .flatten()
+        .map(|function| Finding {
+            rule: FindingRule::UnreachedFunction,
+            message: format!("{} is not reached from any entry point", function.label),
+            location: nodes
+                .get(function.id.as_str())
+                .and_then(|n| FindingLocation::of_node(n)),
+            related: Vec::new(),
+        })
+        .collect()
+}
+
+/// One finding per violating call site (per call when no site is recorded).
+pub fn from_violations(violations: &[Violation]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for violation in violations {
+        let message = format!(
+            "{} calls {} ({} -> {}): {}",
+            violation.from_label,
+            violation.to_label,
+            violation.from_module,
+            violation.to_module,
+            violation.rule
+        );
+        push_per_call_site(
+            &mut findings,
+            FindingRule::LayeringViolation,
+            &message,
+            &violation.call_sites,
+        );
+    }
+    findings
+}
+
+/// One finding per call site of a call missing from the baseline (per call when no site is
+/// recorded).
+pub fn from_new_calls(calls: &[NewCall]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for call in calls {
+        let message = format!(
+            "{} calls {} ({} -> {}), which is not in the baseline",
+            call.from_label, call.to_label, call.from_group, call.to_group
+        );
+        push_per_call_site(
+            &mut findings,
+            FindingRule::NewCall,
+            &message,
+            &call.call_sites,
+        );
+    }
+    findings
+}
+
+fn push_per_call_site(
+    findings: &mut Vec<Finding>,
+    rule: FindingRule,
+    message: &str,
+    call_sites: &[CallSite],
+) {
+    let sites: Vec<Option<FindingLocation>> = if call_sites.is_empty() {
+        vec![None]
+    } else {
+        call_sites
+            .iter()
+            .map(|site| Some(FindingLocation::of_call_site(site)))
+            .collect()
+    };
+    for location in sites {
+        findings.push(Finding {
+            rule,
+            message: message.to_string(),
+            location,
+            related: Vec::new(),
+        });
+    }
+}
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run; 1],
+}
+
+#[derive(Serialize)]
+struct Run {
+    tool: Tool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message,
+    default_configuration: Configuration,
+}
+
+#[derive(Serialize)]
+struct Configuration {
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<u32>,
+}
+
+fn sarif_location(location: &FindingLocation) -> Location {
+    let (uri, uri_base_id) = if location.file.starts_with('/') {
+        (format!("file://{}", location.file), None)
+    } else {
+        (location.file.clone(), Some("%SRCROOT%"))
+    };
+    Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation { uri, uri_base_id },
+            region: Region {
+                start_line: location.start_line,
+                end_line: location.end_line,
+                start_column: location.start_column,
+            },
+        },
+    }
+}
+
+/// A SARIF 2.1.0 log with one run of `gen_callgraph` holding `findings`.
+pub fn to_sarif(findings: &[Finding]) -> String {
+    let rule_index = |rule: FindingRule| {
+        FindingRule::ALL
+            .iter()
+            .position(|&r| r == rule)
+            .expect("ALL lists every rule")
+    };
+    let log = Log {
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        version: "2.1.0",
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules: FindingRule::ALL
+                        .iter()
+                        .map(|&rule| Rule {
+                            id: rule.id(),
+                            short_description: Message {
+                                text: rule.description().to_string(),
+                            },
+                            default_configuration: Configuration {
+                                level: rule.level(),
+                            },
+                        })
+                        .collect(),
+                },
+            },
+            results: findings
+                .iter()
+                .map(|finding| SarifResult {
+                    rule_id: finding.rule.id(),
+                    rule_index: rule_index(finding.rule),
+                    level: finding.rule.level(),
+                    message: Message {
+                        text: finding.message.clone(),
+                    },
+                    locations: finding.location.iter().map(sarif_location).collect(),
+                    related_locations: finding.related.iter().map(sarif_location).collect(),
+                })
+                .collect(),
+        }],
+    };
+    analysis::to_json(&log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{cycles, dead_code};
+    use crate::call_graph::testing::{self, location};
+
+    fn node(id: &str, file: &str, line: u32) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, line, line + 4),
+            ..testing::node(id, id, "")
+        }
+    }
+
+    /// main -> a <-> b; unused is never called
+    fn sample_graph() -> CallGraph {
+        testing::graph(
+            vec![
+                node("main", "src/main.rs", 1),
+                node("a", "src/a.rs", 10),
+                node("b", "/outside/b.rs", 20),
+                node("unused", "src/a.rs", 30),
+            ],
+            &[("main", "a"), ("a", "b"), ("b", "a")],
+        )
+    }
+
+    fn parse(findings: &[Finding]) -> serde_json::Value {
+        serde_json::from_str(&to_sarif(findings)).unwrap()
+    }
+
+    #[test]
+    fn cycle_is_located_at_its_first_function() {
+        let graph = sample_graph();
+        let findings = from_cycles(&graph, &cycles::find_cycles(&graph));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].message, "Mutual recursion between a, b");
+
+        let log = parse(&findings);
+        assert_eq!(log["version"], "2.1.0");
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "recursion-cycle");
+        let physical = &result["locations"][0]["physicalLocation"];
+        assert_eq!(physical["artifactLocation"]["uri"], "src/a.rs");
+        assert_eq!(physical["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        assert_eq!(physical["region"]["startLine"], 10);
+        assert_eq!(physical["region"]["endLine"], 14);
+        let related = &result["relatedLocations"][0]["physicalLocation"]["artifactLocation"];
+        assert_eq!(related["uri"], "file:///outside/b.rs");
+        assert!(related.get("uriBaseId").is_none());
+    }
+
+    #[test]
+    fn dead_functions_become_one_result_each() {
+        let graph = sample_graph();
+        let report = dead_code::find_unreached(&graph, &["main"]);
+        let log = parse(&from_dead_code(&graph, &report));
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "unreached-function");
+        assert_eq!(results[0]["ruleIndex"], 1);
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            30
+        );
+    }
+
+    #[test]
+    fn violations_are_reported_per_call_site() {
+        let violation = Violation {
+            rule: String::from("layer 'lsp' calls higher layer 'app'"),
+            from: String::from("x"),
+            to: String::from("y"),
+            from_label: String::from("send"),
+            to_label: String::from("run"),
+            from_module: String::from("lsp"),
+            to_module: String::from("app"),
+            call_sites: vec![
+                CallSite {
+                    file: String::from("src/lsp.rs"),
+                    line: 7,
+                    column: 9,
+                },
+                CallSite {
+                    file: String::from("src/lsp.rs"),
+                    line: 12,
+                    column: 5,
+                },
+            ],
+        };
+        let findings = from_violations(&[violation]);
+        assert_eq!(findings.len(), 2);
+        let log = parse(&findings);
+        let result = &log["runs"][0]["results"][1];
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["message"]["text"],
+            "send calls run (lsp -> app): layer 'lsp' calls higher layer 'app'"
+        );
+        let region = &result["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 12);
+        assert_eq!(region["startColumn"], 5);
+        assert!(region.get("endLine").is_none());
+        assert_eq!(
+            log["runs"][0]["tool"]["driver"]["rules"][2]["id"],
+            "layering-violation"
+        );
+    }
+
+    #[test]
+    fn new_calls_without_sites_become_one_error() {
+        let call = NewCall {
+            from: String::from("x"),
+            to: String::from("y"),
+            from_label: String::from("App::run"),
+            to_label: String::from("Db::query"),
+            from_group: String::from("App"),
+            to_group: String::from("Db"),
+            call_sites: Vec::new(),
+        };
+        let findings = from_new_calls(&[call]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, FindingRule::NewCall);
+        assert!(findings[0].location.is_none());
+        let log = parse(&findings);
+        assert_eq!(log["runs"][0]["results"][0]["ruleId"], "new-call");
+        assert_eq!(log["runs"][0]["results"][0]["level"], "error");
+    }
+}
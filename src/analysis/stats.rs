@@ -0,0 +1,446 @@
+//! Per-function metrics that point at the hotspots of a call graph.
+//!
+//! [`compute`] measures every node: how many functions call it (fan-in) and it calls
+//! (fan-out), how many functions it reaches transitively, how far it is from the nearest
+//! root, and its betweenness centrality (how many shortest call paths between other
+//! functions run through it, after Brandes). [`top`] sorts by one [`Metric`]; the table is
+//! printed as text, CSV or JSON, and [`scale_nodes`] lets the renderers size nodes by a
+//! metric.
+
+use crate::analysis;
+use crate::call_graph::CallGraph;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// A metric the table can be sorted by and nodes can be scaled by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    FanIn,
+    FanOut,
+    Reach,
+    Depth,
+    Betweenness,
+}
+
+impl Metric {
+    /// Column name in the text table and in CSV.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::FanIn => "fan_in",
+            Metric::FanOut => "fan_out",
+            Metric::Reach => "reach",
+            Metric::Depth => "depth",
+            Metric::Betweenness => "betweenness",
+        }
+    }
+
+    /// Value of the metric for `stats`; `None` for the depth of a node no root reaches.
+    pub fn value(self, stats: &NodeStats) -> Option<f64> {
+        match self {
+            Metric::FanIn => Some(stats.fan_in as f64),
+            Metric::FanOut => Some(stats.fan_out as f64),
+            Metric::Reach => Some(stats.reach as f64),
+            Metric::Depth => stats.depth.map(|d| d as f64),
+            Metric::Betweenness => Some(stats.betweenness),
+        }
+    }
+}
+
+/// Metrics of one node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeStats {
+    pub id: String,
+    pub label: String,
+    pub group: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    /// Distinct callers, not counting the function itself.
+    pub fan_in: usize,
+    /// Distinct callees, not counting the function itself.
+    pub fan_out: usize,
+    /// Functions reachable through calls, not counting the function itself.
+    pub reach: usize,
+    /// Fewest calls from a root (an entry function, or when the graph has none, a function
+    /// nobody calls). `None` when no root reaches the function.
+    pub depth: Option<usize>,
+    /// Number of shortest call paths between other functions that pass through this one,
+    /// split evenly where several shortest paths exist.
+    pub betweenness: f64,
+}
+
+/// Metrics of every node of `graph`, in graph order.
+pub fn compute(graph: &CallGraph) -> Vec<NodeStats> {
+    let index_of: HashMap<&str, usize> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect();
+    let count = graph.nodes.len();
+    let mut callees: Vec<Vec<usize>> = vec![Vec::new(); count];
+    let mut callers: Vec<Vec<usize>> = vec![Vec::new(); count];
+    for edge in &graph.edges {
+        if let (Some(&from), Some(&to)) = (
+            index_of.get(edge.from.as_str()),
+            index_of.get(edge.to.as_str()),
+        ) {
+            if from != to && !callees[from].contains(&to) {
+                callees[from].push(to);
+                callers[to].push(from);
+            }
+        }
+    }
+
+    let mut roots: Vec<usize> = (0..count).filter(|&i| graph.nodes[i].is_root).collect();
+    if roots.is_empty() {
+        roots = (0..count).filter(|&i| callers[i].is_empty()).collect();
+    }
+    let depths = distances(&callees, &roots);
+    let betweenness = betweenness(&callees);
+
+    graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| NodeStats {
+            id: node.id.clone(),
+            label: node.label.clone(),
+            group: node.group.clone(),
+            file: node.location.as_ref().map(|l| l.file.clone()),
+            line: node.location.as_ref().map(|l| l.start_line),
+            fan_in: callers[i].len(),
+            fan_out: callees[i].len(),
+            reach: distances(&callees, &[i]).iter().flatten().count() - 1,
+            depth: depths[i],
+            betweenness: betweenness[i],
+        })
+        .collect()
+}
+
+/// Breadth-first distance of every node from the nearest of `sources`.
+fn distances(callees: &[Vec<usize>], sources: &[usize]) -> Vec<Option<usize>> {
+    let mut distance = vec![None; callees.len()];
+    let mut queue = VecDeque::new();
+    for &source in sources {
+        distance[source] = Some(0);
+        queue.push_back(source);
+    }
+    while let Some(node) = queue.pop_front() {
+        let next = distance[node].map(|d| d + 1);
+        for &callee in &callees[node] {
+            if distance[callee].is_none() {
+                distance[callee] = next;
+                queue.push_back(callee);
+            }
+        }
+    }
+    distance
+}
+
+/// Brandes' algorithm for unweighted directed graphs.
+fn betweenness(callees: &[Vec<usize>]) -> Vec<f64> {
+    let count = callees.len();
+    let mut centrality = vec![0.0; count];
+    for source in 0..count {
+        let mut order = Vec::with_capacity(count);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut paths = vec![0.0_f64; count];
+        let mut distance: Vec<Option<usize>> = vec![None; count];
+        paths[source] = 1.0;
+        distance[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            let next = distance[node].map(|d| d + 1);
+            for &callee in &callees[node] {
+                if distance[callee].is_none() {
+                    distance[callee] = next;
+                    queue.push_back(callee);
+                }
+                if distance[callee] == next {
+                    paths[callee] += paths[node];
+                    predecessors[callee].push(node);
+                }
+            }
+        }
+
+        let mut dependency = vec![0.0_f64; count];
+        for &node in order.iter().rev() {
+            for &predecessor in &predecessors[node] {
+                dependency[predecessor] +=
+                    paths[predecessor] / paths[node] * (1.0 + dependency[node]);
+            }
+            if node != source {
+                centrality[node] += dependency[node];
+            }
+        }
+    }
+    centrality
+}
+
+/// The `limit` nodes with the highest `metric` (all of them when `limit` is 0), highest
+/// first; ties and nodes without a value keep label order at the end.
+pub fn top(stats: &[NodeStats], metric: Metric, limit: usize) -> Vec<NodeStats> {
+    let mut sorted = stats.to_vec();
+    sorted.sort_by(|a, b| {
+        let (a_value, b_value) = (metric.value(a), metric.value(b));
+        b_value
+            .partial_cmp(&a_value)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    if limit > 0 {
+        sorted.truncate(limit);
+    }
+    sorted
+}
+
+/// Sets the display size of every node to its `metric` relative to the largest value, so
+/// the renderers can draw hotspots bigger. Nodes without a value keep the default size.
+pub fn scale_nodes(graph: &mut CallGraph, stats: &[NodeStats], metric: Metric) {
+    let values: HashMap<&str, f64> = stats
+        .iter()
+        .filter_map(|s| Some((s.id.as_str(), metric.value(s)?)))
+        .collect();
+    let max = values.values().copied().fold(0.0, f64::max);
+    if max <= 0.0 {
+        return;
+    }
+    for node in &mut graph.nodes {
+        node.size = values.get(node.id.as_str()).map(|value| value / max);
+    }
+}
+
+const COLUMNS: [Metric; 5] = [
+    Metric::FanIn,
+    Metric::FanOut,
+    Metric::Reach,
+    Metric::Depth,
+    Metric::Betweenness,
+];
+
+fn cell(stats: &NodeStats, metric: Metric) -> String {
+    match (metric, metric.value(stats)) {
+        (Metric::Betweenness, Some(value)) => format!("{:.2}", value),
+        (_, Some(value)) => format!("{}", value),
+        (_, None) => String::from("-"),
+    }
+}
+
+/// Text table of `rows` (as returned by [`top`]) out of `total` nodes.
+///
+/// ```text
+/// Top 2 of 14 function(s) by betweenness
+///
+/// function  group  fan_in  fan_out  reach  depth  betweenness
+/// App::run  App         1        4     12      1        22.50
+/// parse     cli         3        0      0      2         0.00
+/// ```
+pub fn format_text(rows: &[NodeStats], total: usize, sort_by: Metric) -> String {
+    let mut out = format!(
+        "Top {} of {} function(s) by {}\n",
+        rows.len(),
+        total,
+        sort_by.name()
+    );
+    if rows.is_empty() {
+        return out;
+    }
+    let label_width = rows.iter().map(|r| r.label.len()).fold(8, usize::max);
+    let group_width = rows.iter().map(|r| r.group.len()).fold(5, usize::max);
+    out.push_str(&format!(
+        "\n{:<label_width$}  {:<group_width$}",
+        "function", "group"
+    ));
+    for metric in COLUMNS {
+        out.push_str(&format!("  {}", metric.name()));
+    }
+    out.push('\n');
+    for row in rows {
+        let line = format!("{:<label_width$}  {:<group_width$}", row.label, row.group);
+        out.push_str(&line);
+        for metric in COLUMNS {
+            out.push_str(&format!(
+                "  {:>width$}",
+                cell(row, metric),
+                width = metric.name().len()
+            ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// CSV form of `rows`, with a header line:
+/// `id,label,group,file,line,fan_in,fan_out,reach,depth,betweenness`. Unknown values are
+/// left empty.
+pub fn to_csv(rows: &[NodeStats]) -> String {
+    let mut out = String::from("id,label,group,file,line");
+    for metric in COLUMNS {
+        out.push_str(&format!(",{}", metric.name()));
+    }
+    out.push('\n');
+    for row in rows {
+        let mut fields = vec![
+            csv_field(&row.id),
+            csv_field(&row.label),
+            csv_field(&row.group),
+            csv_field(row.file.as_deref().unwrap_or_default()),
+            row.line.map(|l| l.to_string()).unwrap_or_default(),
+        ];
+        fields.extend(
+            COLUMNS
+                .iter()
+                .map(|&metric| match cell(row, metric).as_str() {
+                    "-" => String::new(),
+                    value => value.to_string(),
+                }),
+        );
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quotes `value` when it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// JSON form of `rows`: `{"sort_by": "betweenness", "node_count": N, "nodes": [{"id",
+/// "label", "group", "file", "line", "fan_in", "fan_out", "reach", "depth",
+/// "betweenness"}]}`.
+pub fn to_json(rows: &[NodeStats], total: usize, sort_by: Metric) -> String {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        sort_by: Metric,
+        node_count: usize,
+        nodes: &'a [NodeStats],
+    }
+    analysis::to_json(&Document {
+        sort_by,
+        node_count: total,
+        nodes: rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, node};
+
+    fn graph(ids: &[&str], edges: &[(&str, &str)]) -> CallGraph {
+        testing::graph(ids.iter().map(|id| node(id, id, "app")).collect(), edges)
+    }
+
+    /// main -> {a, b} -> hub -> {x, y}; x calls itself
+    fn hub_graph() -> CallGraph {
+        graph(
+            &["main", "a", "b", "hub", "x", "y"],
+            &[
+                ("main", "a"),
+                ("main", "b"),
+                ("a", "hub"),
+                ("b", "hub"),
+                ("hub", "x"),
+                ("hub", "y"),
+                ("x", "x"),
+            ],
+        )
+    }
+
+    fn by_id(stats: &[NodeStats], id: &str) -> NodeStats {
+        stats.iter().find(|s| s.id == id).unwrap().clone()
+    }
+
+    #[test]
+    fn fan_reach_and_depth_are_counted() {
+        let stats = compute(&hub_graph());
+        let hub = by_id(&stats, "hub");
+        assert_eq!((hub.fan_in, hub.fan_out, hub.reach), (2, 2, 2));
+        assert_eq!(hub.depth, Some(2));
+        let main = by_id(&stats, "main");
+        assert_eq!((main.fan_in, main.reach, main.depth), (0, 5, Some(0)));
+        let x = by_id(&stats, "x");
+        assert_eq!((x.fan_in, x.fan_out), (1, 0));
+    }
+
+    #[test]
+    fn depth_starts_at_marked_roots() {
+        let mut graph = hub_graph();
+        graph.nodes[3].is_root = true;
+        let stats = compute(&graph);
+        assert_eq!(by_id(&stats, "hub").depth, Some(0));
+        assert_eq!(by_id(&stats, "x").depth, Some(1));
+        assert_eq!(by_id(&stats, "main").depth, None);
+    }
+
+    #[test]
+    fn betweenness_counts_shortest_paths_through_a_node() {
+        let stats = compute(&hub_graph());
+        // Paths through hub: {main, a, b} to {x, y}.
+        assert_eq!(by_id(&stats, "hub").betweenness, 6.0);
+        // a and b share the paths from main to hub, x and y.
+        assert_eq!(by_id(&stats, "a").betweenness, 1.5);
+        assert_eq!(by_id(&stats, "main").betweenness, 0.0);
+    }
+
+    #[test]
+    fn top_sorts_by_metric_and_limits() {
+        let stats = compute(&hub_graph());
+        let labels =
+            |rows: Vec<NodeStats>| -> Vec<String> { rows.into_iter().map(|r| r.label).collect() };
+        assert_eq!(
+            labels(top(&stats, Metric::Betweenness, 3)),
+            vec!["hub", "a", "b"]
+        );
+        assert_eq!(labels(top(&stats, Metric::FanOut, 2)), vec!["hub", "main"]);
+        assert_eq!(top(&stats, Metric::Reach, 0).len(), 6);
+    }
+
+    #[test]
+    fn nodes_are_scaled_relative_to_the_largest_value() {
+        let mut graph = hub_graph();
+        let stats = compute(&graph);
+        scale_nodes(&mut graph, &stats, Metric::Reach);
+        assert_eq!(graph.nodes[0].size, Some(1.0));
+        assert_eq!(graph.nodes[3].size, Some(0.4));
+        assert_eq!(graph.nodes[4].size, Some(0.0));
+    }
+
+    #[test]
+    fn table_csv_and_json_list_the_rows() {
+        let stats = compute(&hub_graph());
+        let rows = top(&stats, Metric::Betweenness, 1);
+        assert_eq!(
+            format_text(&rows, 6, Metric::Betweenness),
+            "Top 1 of 6 function(s) by betweenness\n\n\
+             function  group  fan_in  fan_out  reach  depth  betweenness\n\
+             hub       app         2        2      2      2         6.00\n"
+        );
+        assert_eq!(
+            to_csv(&rows),
+            "id,label,group,file,line,fan_in,fan_out,reach,depth,betweenness\n\
+             hub,hub,app,,,2,2,2,2,6.00\n"
+        );
+        let value: serde_json::Value =
+            serde_json::from_str(&to_json(&rows, 6, Metric::Betweenness)).unwrap();
+        assert_eq!(value["sort_by"], "betweenness");
+        assert_eq!(value["nodes"][0]["fan_in"], 2);
+    }
+
+    #[test]
+    fn csv_fields_with_separators_are_quoted() {
+        assert_eq!(
+            csv_field("<Map<K, V> as Fmt>::f"),
+            "\"<Map<K, V> as Fmt>::f\""
+        );
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("plain"), "plain");
+    }
+}
@@ -0,0 +1,269 @@
+//! Which tests exercise which functions.
+//!
+//! Works on a graph that contains every workspace function with the test functions marked
+//! as roots. [`map_tests`] follows the calls of each test and indexes, for every function
+//! of the workspace outside test code, the tests that reach it; functions that no test
+//! reaches are listed by module.
+
+use crate::analysis;
+use crate::call_graph::{CallGraph, CallGraphNode, Highlight};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A function outside test code and the tests that reach it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestedFunction {
+    pub id: String,
+    pub label: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    /// Node ids (function paths) of the tests, sorted.
+    pub tests: Vec<String>,
+}
+
+impl TestedFunction {
+    fn of(node: &CallGraphNode) -> Self {
+        TestedFunction {
+            id: node.id.clone(),
+            label: node.label.clone(),
+            file: node.location.as_ref().map(|l| l.file.clone()),
+            line: node.location.as_ref().map(|l| l.start_line),
+            tests: Vec::new(),
+        }
+    }
+}
+
+/// Result of a test reachability analysis.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TestMap {
+    /// Number of test functions (roots of the graph).
+    pub test_count: usize,
+    /// Functions reached by at least one test, sorted by label.
+    pub functions: Vec<TestedFunction>,
+    /// Functions reached by no test, keyed by module path (see [`analysis::module_path`]),
+    /// each list in graph order.
+    pub untested: BTreeMap<String, Vec<TestedFunction>>,
+}
+
+impl TestMap {
+    pub fn untested_count(&self) -> usize {
+        self.untested.values().map(Vec::len).sum()
+    }
+}
+
+/// Indexes the functions of `graph` by the tests (its roots) that reach them. When `only`
+/// is not empty, the functions are restricted to those node ids.
+pub fn map_tests(graph: &CallGraph, only: &[&str]) -> TestMap {
+    let mut tests_of: HashMap<&str, Vec<String>> = HashMap::new();
+    let tests: Vec<&CallGraphNode> = graph.nodes.iter().filter(|n| n.is_root).collect();
+    for test in &tests {
+        for id in analysis::reachable_from(graph, &[test.id.as_str()]) {
+            tests_of.entry(id).or_default().push(test.id.clone());
+        }
+    }
+
+    let only: HashSet<&str> = only.iter().copied().collect();
+    let mut map = TestMap {
+        test_count: tests.len(),
+        ..Default::default()
+    };
+    for node in &graph.nodes {
+        if node.is_test || node.external || (!only.is_empty() && !only.contains(node.id.as_str())) {
+            continue;
+        }
+        let mut function = TestedFunction::of(node);
+        match tests_of.remove(node.id.as_str()) {
+            Some(mut tests) => {
+                tests.sort();
+                function.tests = tests;
+                map.functions.push(function);
+            }
+            None => {
+                let module =
+                    analysis::module_path(node).unwrap_or_else(|| String::from("(unknown)"));
+                map.untested.entry(module).or_default().push(function);
+            }
+        }
+    }
+    map.functions
+        .sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+    map
+}
+
+/// Highlights every function no test reaches as [`Highlight::Unreached`].
+pub fn mark_untested(graph: &mut CallGraph, map: &TestMap) {
+    let ids: HashSet<&str> = map
+        .untested
+        .values()
+        .flatten()
+        .map(|f| f.id.as_str())
+        .collect();
+    for node in &mut graph.nodes {
+        if ids.contains(node.id.as_str()) {
+            node.highlight = Some(Highlight::Unreached);
+        }
+    }
+}
+
+/// Text form of the map: the tests of each function, then the untested functions by module.
+///
+/// ```text
+/// 1 function(s) reached by 2 test(s), 1 reached by none
+///
+/// Tests per function:
+///   App::run  src/app.rs:10
+///     app::tests::runs
+///     cli::parses_flags
+///
+/// Reached by no test:
+/// lsp::client
+///   LspClient::old_request  src/lsp/client.rs:120
+/// ```
+pub fn format_text(map: &TestMap) -> String {
+    let mut out = format!(
+        "{} function(s) reached by {} test(s), {} reached by none\n",
+        map.functions.len(),
+        map.test_count,
+        map.untested_count()
+    );
+    if !map.functions.is_empty() {
+        out.push_str("\nTests per function:\n");
+        for function in &map.functions {
+            out.push_str(&format!("  {}\n", located(function)));
+            for test in &function.tests {
+                out.push_str(&format!("    {}\n", test));
+            }
+        }
+    }
+    if !map.untested.is_empty() {
+        out.push_str("\nReached by no test:\n");
+        for (module, functions) in &map.untested {
+            out.push_str(&format!("{}\n", module));
+            for function in functions {
+                out.push_str(&format!("  {}\n", located(function)));
+            }
+        }
+    }
+    out
+}
+
+fn located(function: &TestedFunction) -> String {
+    match (&function.file, function.line) {
+        (Some(file), Some(line)) => format!("{}  {}:{}", function.label, file, line),
+        _ => function.label.clone(),
+    }
+}
+
+/// JSON form of the map: `{"test_count": N, "functions": [{"id", "label", "file", "line",
+/// "tests": [...]}], "untested": {"module": [...]}, "untested_count": N}`.
+pub fn to_json(map: &TestMap) -> String {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        #[serde(flatten)]
+        map: &'a TestMap,
+        untested_count: usize,
+    }
+    analysis::to_json(&Document {
+        map,
+        untested_count: map.untested_count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+
+    fn node(id: &str, label: &str, file: &str, line: u32) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, line, line + 5),
+            ..testing::node(id, label, "")
+        }
+    }
+
+    fn test(id: &str, label: &str, file: &str, line: u32) -> CallGraphNode {
+        CallGraphNode {
+            is_test: true,
+            is_root: true,
+            ..node(id, label, file, line)
+        }
+    }
+
+    /// runs -> App::run -> parse; parses_flags -> parse; fixture (test helper); old untested
+    fn graph() -> CallGraph {
+        let mut fixture = node("fixture", "tests::fixture", "src/app.rs", 90);
+        fixture.is_test = true;
+        testing::graph(
+            vec![
+                test("app::tests::runs", "tests::runs", "src/app.rs", 80),
+                test(
+                    "tests::cli::parses_flags",
+                    "parses_flags",
+                    "tests/cli.rs",
+                    3,
+                ),
+                node("run", "App::run", "src/app.rs", 10),
+                node("parse", "parse", "src/cli.rs", 5),
+                node("old", "old", "src/cli.rs", 40),
+                fixture,
+            ],
+            &[
+                ("app::tests::runs", "run"),
+                ("app::tests::runs", "fixture"),
+                ("run", "parse"),
+                ("tests::cli::parses_flags", "parse"),
+            ],
+        )
+    }
+
+    #[test]
+    fn functions_are_indexed_by_the_tests_reaching_them() {
+        let map = map_tests(&graph(), &[]);
+        assert_eq!(map.test_count, 2);
+        let index: Vec<(&str, Vec<&str>)> = map
+            .functions
+            .iter()
+            .map(|f| {
+                (
+                    f.label.as_str(),
+                    f.tests.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            index,
+            vec![
+                ("App::run", vec!["app::tests::runs"]),
+                (
+                    "parse",
+                    vec!["app::tests::runs", "tests::cli::parses_flags"]
+                ),
+            ]
+        );
+        let untested: Vec<&str> = map.untested["cli"].iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(untested, vec!["old"]);
+        assert_eq!(map.untested_count(), 1);
+    }
+
+    #[test]
+    fn index_can_be_restricted_to_given_functions() {
+        let map = map_tests(&graph(), &["parse"]);
+        assert_eq!(map.functions.len(), 1);
+        assert_eq!(map.functions[0].label, "parse");
+        assert!(map.untested.is_empty());
+    }
+
+    #[test]
+    fn text_lists_tests_and_untested_functions() {
+        let mut graph = graph();
+        let map = map_tests(&graph, &[]);
+        let text = format_text(&map);
+        assert!(text.starts_with("2 function(s) reached by 2 test(s), 1 reached by none\n"));
+        assert!(text.contains("  App::run  src/app.rs:10\n    app::tests::runs\n"));
+        assert!(text.ends_with("\nReached by no test:\ncli\n  old  src/cli.rs:40\n"));
+
+        mark_untested(&mut graph, &map);
+        assert_eq!(graph.nodes[4].highlight, Some(Highlight::Unreached));
+        assert!(graph.nodes[3].highlight.is_none());
+    }
+}
@@ -1,45 +1,476 @@
 //! Orchestration layer. Wires `cli::Config` → `LspSession` → `CallGraphBuilder` →
-//! `DotRenderer` → file write.
+//! `Renderer` → file (or stdout) write. When `--input` is given, the graph is loaded from JSON
+//! instead and no LSP session is started. Analyses (such as `--path-to`) run on the finished
+//! graph before it is rendered, and recursion cycles are always highlighted.
 //!
 //! This module contains no domain logic. It is the only place in the codebase that is
 //! allowed to connect the independent layers (CLI, LSP session, builder, renderer) together.
 
 use std::fs;
 
-use crate::call_graph_builder::CallGraphBuilder;
-use crate::cli::Config;
+use crate::analysis::baseline::{self, Scope};
+use crate::analysis::dead_code::{self, RootSelection};
+use crate::analysis::diff;
+use crate::analysis::impact;
+use crate::analysis::layering::{self, LayerRules};
+use crate::analysis::modules::{self, Collapse};
+use crate::analysis::stats::{self, Metric as StatsMetric};
+use crate::analysis::{self, cycles, paths, sarif, test_map};
+use crate::call_graph::entry_spec::EntrySpec;
+use crate::call_graph::filter::{self, NodeFilter, Rule};
+use crate::call_graph::CallGraph;
+use crate::call_graph_builder::{CallGraphBuilder, ExternalCalls, TraversalOptions};
+use crate::cli::{
+    BaselineScope, CollapseBy, Config, Direction, ExternalCallees, FilterMode, FilterSpec,
+    ImpactSource, Metric, PathMode, Report, ReportFormat, RootKind,
+};
+use crate::error::{CallGraphError, FilterError};
+use crate::git_diff::{self, FileChange};
+use crate::lsp;
 use crate::lsp_session::LspSession;
+use crate::renderer::{self, Renderer, STDOUT_PATH};
 
 pub async fn run(config: Config) -> anyhow::Result<()> {
-    let mut session = LspSession::start(&config).await?;
-
-    let graph_result = {
-        let mut builder = CallGraphBuilder::new(session.client_mut());
-        match &config.entry_function {
-            Some(entry) => {
-                println!("Generating call graph for entry function: {}", entry);
-                builder.generate_call_graph(entry).await
+    let output_path = config.output_path.as_deref();
+    let renderer = renderer::select(config.format.as_deref(), output_path.unwrap_or_default())?;
+    let node_filter = compile_filter(&config.filter)?;
+    let layer_rules = config.rules_path.as_deref().map(load_rules).transpose()?;
+    let changes = config
+        .impact
+        .as_ref()
+        .map(|source| read_changes(source, &config.workspace))
+        .transpose()?;
+
+    // While traversing, the builder applies the filter itself; reconnecting filters the
+    // finished graph. A loaded graph is never traversed, so the CLI requires reconnecting.
+    let (traversal_filter, post_filter) = match config.filter_mode {
+        FilterMode::Traverse => (node_filter, NodeFilter::default()),
+        FilterMode::Reconnect => (NodeFilter::default(), node_filter),
+    };
+
+    let graph_result = match &config.input_path {
+        Some(input) => load_graph(input),
+        None => {
+            let mut session = LspSession::start(&config).await?;
+            let result = build_graph(
+                session.client_mut(),
+                &config,
+                traversal_filter,
+                changes.as_deref(),
+            )
+            .await;
+            session.shutdown().await;
+            result.map_err(anyhow::Error::from)
+        }
+    };
+    let graph_result = graph_result.map(|graph| {
+        if post_filter.is_empty() {
+            graph
+        } else {
+            filter::remove_and_reconnect(graph, &post_filter)
+        }
+    });
+
+    let graph =
+        graph_result.map_err(|e| anyhow::anyhow!("cannot build the call graph: {:#}", e))?;
+
+    let mut graph = match &config.path_to {
+        Some(target) => select_paths(graph, &config.entry_functions, target, output_path, &config)?,
+        None => graph,
+    };
+    // The baseline keeps function-level calls, so it can be collapsed like the graph.
+    if config.update_baseline {
+        if let Some(path) = &config.baseline_path {
+            fs::write(path, crate::graph_json::to_json(&graph))
+                .map_err(|e| anyhow::anyhow!("cannot write baseline '{}': {}", path, e))?;
+            eprintln!("Baseline updated: {}", path);
+        }
+    }
+    if let Some(by) = config.collapse {
+        graph = modules::collapse(&graph, collapse_mode(by, config.module_depth));
+    }
+
+    if let Some(baseline) = &config.diff_against {
+        let old = load_snapshot(baseline, &config)?;
+        let changes = diff::diff(&old, &graph);
+        let text = match config.report_format {
+            ReportFormat::Json => diff::to_json(&changes),
+            _ => diff::format_text(&changes),
+        };
+        print_report(&text, output_path);
+        graph = diff::combined_graph(&old, &graph, &changes);
+    }
+
+    let cycles = cycles::find_cycles(&graph);
+    // In a diff, red already means "removed".
+    if config.diff_against.is_none() {
+        cycles::mark_cycles(&mut graph, &cycles);
+    }
+
+    if config.impact.is_some() {
+        let report = impact::analyze(&graph);
+        impact::mark_changed(&mut graph);
+        let text = match config.report_format {
+            ReportFormat::Json => impact::to_json(&report),
+            _ => impact::format_text(&report),
+        };
+        print_report(&text, output_path);
+    }
+
+    let mut failure = None;
+    match config.report {
+        Some(Report::Cycles) => {
+            let report = match config.report_format {
+                ReportFormat::Json => cycles::to_json(&graph, &cycles),
+                ReportFormat::Sarif => sarif::to_sarif(&sarif::from_cycles(&graph, &cycles)),
+                _ => cycles::format_cycle_report(&graph, &cycles),
+            };
+            print_report(&report, output_path);
+        }
+        Some(Report::DeadCode) => {
+            let roots = dead_code::select_roots(&graph, &root_selection(&config, &graph)?);
+            let report = dead_code::find_unreached(&graph, &roots);
+            dead_code::mark_unreached(&mut graph, &report);
+            let text = match config.report_format {
+                ReportFormat::Json => dead_code::to_json(&report),
+                ReportFormat::Sarif => sarif::to_sarif(&sarif::from_dead_code(&graph, &report)),
+                _ => dead_code::format_text(&report),
+            };
+            print_report(&text, output_path);
+        }
+        Some(Report::Layering) => {
+            let rules = layer_rules.unwrap_or_default();
+            let violations = layering::check(&graph, &rules);
+            layering::mark_violations(&mut graph, &violations);
+            let text = match config.report_format {
+                ReportFormat::Json => layering::to_json(&violations),
+                ReportFormat::Sarif => sarif::to_sarif(&sarif::from_violations(&violations)),
+                _ => layering::format_text(&violations),
+            };
+            print_report(&text, output_path);
+            if !violations.is_empty() {
+                failure = Some(format!(
+                    "{} call(s) break the architecture rules",
+                    violations.len()
+                ));
             }
-            None => {
-                println!("No entry function specified. Generating call graph for all symbols.");
-                builder.generate_call_graph_all().await
+        }
+        Some(Report::Baseline) => {
+            let path = config.baseline_path.as_deref().unwrap_or_default();
+            let accepted = load_snapshot(path, &config)?;
+            let scope = match config.baseline_scope {
+                BaselineScope::All => Scope::All,
+                BaselineScope::CrossGroup => Scope::CrossGroup,
+            };
+            let calls = baseline::new_calls(&accepted, &graph, scope);
+            baseline::mark_new_calls(&mut graph, &calls);
+            let text = match config.report_format {
+                ReportFormat::Json => baseline::to_json(&calls),
+                ReportFormat::Sarif => sarif::to_sarif(&sarif::from_new_calls(&calls)),
+                _ => baseline::format_text(&calls),
+            };
+            print_report(&text, output_path);
+            if !calls.is_empty() {
+                failure = Some(format!(
+                    "{} call(s) are not in the baseline; accept them with --update-baseline",
+                    calls.len()
+                ));
             }
         }
-    };
+        Some(Report::Tests) => {
+            let only = find_entries(&graph, &config.entry_functions)?;
+            let map = test_map::map_tests(&graph, &only);
+            test_map::mark_untested(&mut graph, &map);
+            let text = match config.report_format {
+                ReportFormat::Json => test_map::to_json(&map),
+                _ => test_map::format_text(&map),
+            };
+            print_report(&text, output_path);
+        }
+        Some(Report::Stats) => {
+            let metric = stats_metric(config.sort_by);
+            let rows = stats::top(&stats::compute(&graph), metric, config.top);
+            let text = match config.report_format {
+                ReportFormat::Json => stats::to_json(&rows, graph.nodes.len(), metric),
+                ReportFormat::Csv => stats::to_csv(&rows),
+                _ => stats::format_text(&rows, graph.nodes.len(), metric),
+            };
+            print_report(&text, output_path);
+        }
+        None => {}
+    }
+    if let Some(metric) = config.scale_nodes {
+        let node_stats = stats::compute(&graph);
+        stats::scale_nodes(&mut graph, &node_stats, stats_metric(metric));
+    }
+
+    if let Some(path) = output_path {
+        write_output(path, renderer, &graph)?;
+    }
+    if let Some(message) = failure {
+        return Err(anyhow::anyhow!(message));
+    }
+    Ok(())
+}
+
+/// Maps `--collapse` and `--module-depth` to the aggregation mode.
+fn collapse_mode(by: CollapseBy, module_depth: Option<usize>) -> Collapse {
+    match by {
+        CollapseBy::Group => Collapse::Group,
+        CollapseBy::Module => Collapse::ModulePath {
+            depth: module_depth,
+        },
+    }
+}
+
+/// Maps `--sort-by` and `--scale-nodes` to the stats metric.
+fn stats_metric(metric: Metric) -> StatsMetric {
+    match metric {
+        Metric::FanIn => StatsMetric::FanIn,
+        Metric::FanOut => StatsMetric::FanOut,
+        Metric::Reach => StatsMetric::Reach,
+        Metric::Depth => StatsMetric::Depth,
+        Metric::Betweenness => StatsMetric::Betweenness,
+    }
+}
+
+/// Maps `--roots` and the entry functions to the dead-code root selection.
+fn root_selection(config: &Config, graph: &CallGraph) -> anyhow::Result<RootSelection> {
+    Ok(RootSelection {
+        main: config.roots.contains(&RootKind::Main),
+        public: config.roots.contains(&RootKind::Pub),
+        tests: config.roots.contains(&RootKind::Tests),
+        ids: find_entries(graph, &config.entry_functions)?
+            .into_iter()
+            .map(String::from)
+            .collect(),
+    })
+}
 
-    match graph_result {
-        Ok(graph) => {
-            let dot = crate::dot_renderer::to_dot(&graph);
-            if let Err(e) = fs::write(&config.output_path, dot) {
-                eprintln!("DOT write Error: {:?}", e);
+/// Ids of the nodes of `graph` that the entry specs name, failing on a spec that names none.
+fn find_entries<'g>(graph: &'g CallGraph, specs: &[String]) -> anyhow::Result<Vec<&'g str>> {
+    let mut ids = Vec::new();
+    for spec in specs {
+        let found = find_entry_nodes(graph, spec);
+        if found.is_empty() {
+            return Err(anyhow::anyhow!("'{}' is not in the graph", spec));
+        }
+        ids.extend(found);
+    }
+    Ok(ids)
+}
+
+/// Ids of the nodes of `graph` that the entry spec `spec` names, in any of the forms
+/// [`EntrySpec`] accepts.
+fn find_entry_nodes<'g>(graph: &'g CallGraph, spec: &str) -> Vec<&'g str> {
+    match EntrySpec::parse(spec) {
+        EntrySpec::Location { file, line } => analysis::find_node_at(graph, &file, line)
+            .into_iter()
+            .collect(),
+        EntrySpec::Qualified { .. } => {
+            let by_path = analysis::find_nodes_by_path(graph, spec);
+            if by_path.is_empty() {
+                analysis::find_nodes_by_name(graph, spec)
             } else {
-                println!("DOT output Success: {}", config.output_path);
+                by_path
             }
         }
-        Err(e) => eprintln!("Call graph Error: {:?}", e),
+        EntrySpec::Name(name) => analysis::find_nodes_by_name(graph, &name),
+    }
+}
+
+/// Narrows `graph` to the call paths from any of `sources` to `target` and prints them as
+/// call chains.
+fn select_paths(
+    graph: CallGraph,
+    sources: &[String],
+    target: &str,
+    output_path: Option<&str>,
+    config: &Config,
+) -> anyhow::Result<CallGraph> {
+    let source_names = sources.join(", ");
+    let source_ids =
+        find_entries(&graph, sources).map_err(|e| anyhow::anyhow!("path source {}", e))?;
+    let targets = find_entry_nodes(&graph, target);
+    if targets.is_empty() {
+        return Err(anyhow::anyhow!(
+            "path target '{}' is not reachable from '{}'",
+            target,
+            source_names
+        ));
+    }
+
+    let found = match config.path_mode {
+        PathMode::Shortest => paths::shortest_path(&graph, &source_ids, &targets)
+            .into_iter()
+            .collect(),
+        PathMode::All => paths::simple_paths(&graph, &source_ids, &targets, config.max_paths),
+    };
+    if found.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no call path from '{}' to '{}'",
+            source_names,
+            target
+        ));
     }
 
-    session.shutdown().await;
+    print_report(&paths::format_paths(&graph, &found), output_path);
+    Ok(paths::path_subgraph(&graph, &found))
+}
 
+/// Prints a textual report on stdout, or on stderr when stdout already carries the graph.
+fn print_report(report: &str, output_path: Option<&str>) {
+    if output_path == Some(STDOUT_PATH) {
+        eprint!("{}", report);
+    } else {
+        print!("{}", report);
+    }
+}
+
+/// Renders `graph` and writes it to `output_path`, or to stdout when the path is `-`.
+///
+/// Progress and status messages go to stderr so that stdout carries only the rendered
+/// graph and can be piped into other tools.
+fn write_output(
+    output_path: &str,
+    renderer: &dyn Renderer,
+    graph: &CallGraph,
+) -> anyhow::Result<()> {
+    let rendered = renderer.render(graph);
+    if output_path == STDOUT_PATH {
+        print!("{}", rendered);
+        return Ok(());
+    }
+    fs::write(output_path, rendered).map_err(|e| {
+        anyhow::anyhow!(
+            "cannot write {} output '{}': {}",
+            renderer.name(),
+            output_path,
+            e
+        )
+    })?;
+    eprintln!("{} output Success: {}", renderer.name(), output_path);
     Ok(())
 }
+
+/// Reads a graph previously saved with the JSON output format.
+fn load_graph(input: &str) -> anyhow::Result<CallGraph> {
+    eprintln!("Loading call graph from: {}", input);
+    let text = fs::read_to_string(input)?;
+    crate::graph_json::from_json(&text)
+}
+
+/// Reads a saved graph to compare against, collapsed like the current graph.
+fn load_snapshot(path: &str, config: &Config) -> anyhow::Result<CallGraph> {
+    let graph = load_graph(path)?;
+    Ok(match config.collapse {
+        Some(by) => modules::collapse(&graph, collapse_mode(by, config.module_depth)),
+        None => graph,
+    })
+}
+
+/// Reads the changed lines of `--impact-patch` or `--impact-revs`.
+fn read_changes(source: &ImpactSource, workspace: &str) -> anyhow::Result<Vec<FileChange>> {
+    let text = match source {
+        ImpactSource::Patch(path) => fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("cannot read patch file '{}': {}", path, e))?,
+        ImpactSource::Revisions(revisions) => {
+            git_diff::git_diff(std::path::Path::new(workspace), revisions)?
+        }
+    };
+    Ok(git_diff::parse_unified_diff(&text))
+}
+
+/// Reads and parses the `--rules` file.
+fn load_rules(path: &str) -> anyhow::Result<LayerRules> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("cannot read rules file '{}': {}", path, e))?;
+    Ok(LayerRules::from_toml(&text)?)
+}
+
+/// Compiles the `--include-*` / `--exclude-*` patterns.
+fn compile_filter(spec: &FilterSpec) -> Result<NodeFilter, FilterError> {
+    fn rules(
+        files: &[String],
+        names: &[String],
+        groups: &[String],
+    ) -> Result<Vec<Rule>, FilterError> {
+        let mut rules = Vec::new();
+        for pattern in files {
+            rules.push(Rule::file(pattern)?);
+        }
+        for pattern in names {
+            rules.push(Rule::name(pattern)?);
+        }
+        rules.extend(groups.iter().map(|group| Rule::group(group)));
+        Ok(rules)
+    }
+    Ok(NodeFilter {
+        include: rules(
+            &spec.include_files,
+            &spec.include_names,
+            &spec.include_groups,
+        )?,
+        exclude: rules(
+            &spec.exclude_files,
+            &spec.exclude_names,
+            &spec.exclude_groups,
+        )?,
+    })
+}
+
+/// Builds the graph requested by `config` against an initialized LSP session: the impact
+/// graph of `changes` when given.
+async fn build_graph(
+    client: &mut lsp::LspClient,
+    config: &Config,
+    filter: NodeFilter,
+    changes: Option<&[FileChange]>,
+) -> Result<CallGraph, CallGraphError> {
+    let mut builder = CallGraphBuilder::new(client).with_options(TraversalOptions {
+        max_depth: config.max_depth,
+        filter,
+        expand_dispatch: config.expand_dispatch,
+        external: ExternalCalls {
+            std: config.external.contains(&ExternalCallees::Std),
+            dependencies: config.external.contains(&ExternalCallees::Deps),
+        },
+    });
+    if let Some(changes) = changes {
+        eprintln!(
+            "Generating caller graph of {} changed file(s)",
+            changes.len()
+        );
+        return builder.generate_impact_graph(changes).await;
+    }
+    // Dead code can only be found in a graph that contains every function; the entry
+    // functions are then roots of the analysis instead of starts of traversal.
+    let entries: &[String] = match config.report {
+        Some(Report::DeadCode) => &[],
+        Some(Report::Tests) => {
+            eprintln!("Generating call graph of all symbols, starting from the tests.");
+            return builder.generate_test_graph().await;
+        }
+        _ => &config.entry_functions,
+    };
+    match entries {
+        [] => {
+            eprintln!("No entry function specified. Generating call graph for all symbols.");
+            builder.generate_call_graph_all().await
+        }
+        targets if config.direction == Direction::Callers => {
+            eprintln!(
+                "Generating caller graph for target function(s): {}",
+                targets.join(", ")
+            );
+            builder.generate_caller_graph(targets).await
+        }
+        entries => {
+            eprintln!(
+                "Generating call graph for entry function(s): {}",
+                entries.join(", ")
+            );
+            builder.generate_call_graph(entries).await
+        }
+    }
+}
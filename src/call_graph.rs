@@ -1,5 +1,13 @@
+pub mod entry_spec;
+pub mod external;
+pub mod filter;
+pub mod identity;
 pub mod meta_resolver;
 pub mod model;
 pub mod symbol_locator;
+#[cfg(test)]
+pub(crate) mod testing;
 
-pub use model::{CallGraph, CallGraphEdge, CallGraphNode};
+pub use model::{
+    CallGraph, CallGraphEdge, CallGraphNode, CallSite, FunctionKind, Highlight, SourceLocation,
+};
@@ -0,0 +1,422 @@
+//! Parsing and matching of entry function specs given on the command line.
+//!
+//! An entry spec is one of:
+//! - a bare name: `initialize`
+//! - a qualified path: `LspClient::initialize` or
+//!   `crate::lsp::lsp_client::LspClient::initialize`
+//! - a source location: `src/app.rs:13` (any line inside the function)
+//!
+//! A workspace function's qualified path is its module path (derived from its file, see
+//! [`meta_resolver::module_segments`]) followed by its container name when that differs from
+//! the last module segment. A qualified spec matches when its qualifier is a suffix of that
+//! path; a spec starting with `crate::` or the crate name must match the whole path.
+
+use crate::call_graph::meta_resolver;
+use lsp_types::{DocumentSymbol, SymbolInformation, SymbolKind, Url};
+use std::path::Path;
+
+/// A parsed entry function spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum EntrySpec {
+    /// Bare function name.
+    Name(String),
+    /// Function name with the path segments before it.
+    Qualified {
+        qualifier: Vec<String>,
+        name: String,
+    },
+    /// Workspace-relative file and 1-based line.
+    Location { file: String, line: u32 },
+}
+
+impl EntrySpec {
+    pub(crate) fn parse(spec: &str) -> Self {
+        if let Some((file, line)) = spec.rsplit_once(':') {
+            if file.ends_with(".rs") {
+                if let Ok(line) = line.parse::<u32>() {
+                    return EntrySpec::Location {
+                        file: file.trim_start_matches("./").to_string(),
+                        line,
+                    };
+                }
+            }
+        }
+        let mut segments: Vec<String> = spec.split("::").map(str::to_string).collect();
+        let name = segments.pop().unwrap_or_default();
+        if segments.is_empty() {
+            EntrySpec::Name(name)
+        } else {
+            EntrySpec::Qualified {
+                qualifier: segments,
+                name,
+            }
+        }
+    }
+
+    /// The function name to search for, when the spec has one.
+    pub(crate) fn name(&self) -> Option<&str> {
+        match self {
+            EntrySpec::Name(name) | EntrySpec::Qualified { name, .. } => Some(name),
+            EntrySpec::Location { .. } => None,
+        }
+    }
+}
+
+fn is_function(symbol: &SymbolInformation) -> bool {
+    symbol.kind == SymbolKind::FUNCTION || symbol.kind == SymbolKind::METHOD
+}
+
+/// Qualified path of `symbol`, without the crate name: module segments, then the container.
+pub(crate) fn qualified_path(
+    symbol: &SymbolInformation,
+    workspace_root_path: &Path,
+) -> Vec<String> {
+    let rel = meta_resolver::workspace_relative_path(&symbol.location.uri, workspace_root_path);
+    let mut path = meta_resolver::module_segments(Path::new(&rel)).unwrap_or_default();
+    if let Some(container) = &symbol.container_name {
+        if path.last() != Some(container) {
+            path.push(container.clone());
+        }
+    }
+    path.push(symbol.name.clone());
+    path
+}
+
+/// Functions among `symbols` that `spec` names. Never matches [`EntrySpec::Location`]; those
+/// are resolved through document symbols instead.
+pub(crate) fn matching_symbols<'s>(
+    spec: &EntrySpec,
+    symbols: &'s [SymbolInformation],
+    workspace_root_path: &Path,
+    crate_name: &str,
+) -> Vec<&'s SymbolInformation> {
+    let Some(name) = spec.name() else {
+        return Vec::new();
+    };
+    symbols
+        .iter()
+        .filter(|s| is_function(s) && s.name == name)
+        .filter(|s| match spec {
+            EntrySpec::Qualified { qualifier, .. } => {
+                let path = qualified_path(s, workspace_root_path);
+                let parents = &path[..path.len() - 1];
+                match qualifier.split_first() {
+                    Some((first, rest)) if first == "crate" || first == crate_name => {
+                        parents == rest
+                    }
+                    _ => parents.ends_with(qualifier),
+                }
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// Describes a candidate for ambiguity errors: `lsp::LspClient::new (src/lsp.rs:42)`.
+pub(crate) fn describe_candidate(symbol: &SymbolInformation, workspace_root_path: &Path) -> String {
+    format!(
+        "{} ({}:{})",
+        qualified_path(symbol, workspace_root_path).join("::"),
+        meta_resolver::workspace_relative_path(&symbol.location.uri, workspace_root_path),
+        symbol.location.range.start.line + 1
+    )
+}
+
+/// Up to `limit` workspace function names close to `name`, best first: names within a small
+/// edit distance, or containing `name` (or contained in it), compared case-insensitively.
+pub(crate) fn suggest_names(
+    name: &str,
+    symbols: &[SymbolInformation],
+    limit: usize,
+) -> Vec<String> {
+    let wanted = name.to_lowercase();
+    let max_distance = (wanted.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &str)> = symbols
+        .iter()
+        .filter(|s| is_function(s) && s.name != name)
+        .filter_map(|s| {
+            let candidate = s.name.to_lowercase();
+            let distance = edit_distance(&wanted, &candidate);
+            let related = candidate.contains(&wanted) || wanted.contains(&candidate);
+            (distance <= max_distance || related).then_some((distance, s.name.as_str()))
+        })
+        .collect();
+    scored.sort();
+    scored.dedup_by_key(|(_, name)| *name);
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between two strings, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The innermost function in a document symbol tree whose full range contains the 0-based
+/// `line`, as a [`SymbolInformation`] located at its name.
+pub(crate) fn function_containing_line(
+    symbols: &[DocumentSymbol],
+    line: u32,
+    uri: &Url,
+) -> Option<SymbolInformation> {
+    for sym in symbols {
+        if sym.range.start.line > line || sym.range.end.line < line {
+            continue;
+        }
+        let inner = sym
+            .children
+            .as_deref()
+            .and_then(|children| function_containing_line(children, line, uri));
+        if inner.is_some() {
+            return inner;
+        }
+        if sym.kind == SymbolKind::FUNCTION || sym.kind == SymbolKind::METHOD {
+            return Some(SymbolInformation {
+                name: sym.name.clone(),
+                kind: sym.kind,
+                tags: sym.tags.clone(),
+                #[allow(deprecated)]
+                deprecated: sym.deprecated,
+                location: lsp_types::Location {
+                    uri: uri.clone(),
+                    range: sym.selection_range,
+                },
+                container_name: None,
+            });
+        }
+    }
+    None
+}
+
+/// The innermost functions touched by any of the 1-based inclusive line `ranges`, each
+/// once, in order of first touch.
+pub(crate) fn functions_in_line_ranges(
+    symbols: &[DocumentSymbol],
+    ranges: &[(u32, u32)],
+    uri: &Url,
+) -> Vec<SymbolInformation> {
+    let mut found: Vec<SymbolInformation> = Vec::new();
+    for &(start, end) in ranges {
+        for line in start..=end {
+            let Some(function) = function_containing_line(symbols, line.saturating_sub(1), uri)
+            else {
+                continue;
+            };
+            if !found.iter().any(|f| f.location == function.location) {
+                found.push(function);
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Location, Position, Range};
+
+    const ROOT: &str = "/ws";
+
+    #[allow(deprecated)]
+    fn symbol(name: &str, file: &str, line: u32, container: Option<&str>) -> SymbolInformation {
+        let pos = Position { line, character: 4 };
+        SymbolInformation {
+            name: name.to_string(),
+            kind: SymbolKind::METHOD,
+            tags: None,
+            deprecated: None,
+            location: Location {
+                uri: Url::parse(&format!("file://{}/{}", ROOT, file)).unwrap(),
+                range: Range {
+                    start: pos,
+                    end: pos,
+                },
+            },
+            container_name: container.map(str::to_string),
+        }
+    }
+
+    fn sample_symbols() -> Vec<SymbolInformation> {
+        vec![
+            symbol("new", "src/app.rs", 9, Some("App")),
+            symbol("new", "src/lsp/lsp_client.rs", 41, Some("LspClient")),
+            symbol("initialize", "src/lsp/lsp_client.rs", 60, Some("LspClient")),
+            symbol("run", "src/main.rs", 3, None),
+        ]
+    }
+
+    fn names(found: &[&SymbolInformation]) -> Vec<String> {
+        found
+            .iter()
+            .map(|s| qualified_path(s, Path::new(ROOT)).join("::"))
+            .collect()
+    }
+
+    #[test]
+    fn parse_recognises_all_spec_forms() {
+        assert_eq!(
+            EntrySpec::parse("main"),
+            EntrySpec::Name("main".to_string())
+        );
+        assert_eq!(
+            EntrySpec::parse("MyStruct::new"),
+            EntrySpec::Qualified {
+                qualifier: vec!["MyStruct".to_string()],
+                name: "new".to_string()
+            }
+        );
+        assert_eq!(
+            EntrySpec::parse("./src/app.rs:13"),
+            EntrySpec::Location {
+                file: "src/app.rs".to_string(),
+                line: 13
+            }
+        );
+    }
+
+    #[test]
+    fn bare_name_matches_every_function_with_that_name() {
+        let symbols = sample_symbols();
+        let found = matching_symbols(&EntrySpec::parse("new"), &symbols, Path::new(ROOT), "demo");
+        assert_eq!(
+            names(&found),
+            vec!["app::App::new", "lsp::lsp_client::LspClient::new"]
+        );
+    }
+
+    #[test]
+    fn qualifier_selects_by_container_or_module_suffix() {
+        let symbols = sample_symbols();
+        let root = Path::new(ROOT);
+        let by_type = matching_symbols(&EntrySpec::parse("LspClient::new"), &symbols, root, "demo");
+        assert_eq!(names(&by_type), vec!["lsp::lsp_client::LspClient::new"]);
+        let by_module =
+            matching_symbols(&EntrySpec::parse("app::App::new"), &symbols, root, "demo");
+        assert_eq!(names(&by_module), vec!["app::App::new"]);
+        let by_root_fn = matching_symbols(&EntrySpec::parse("crate::run"), &symbols, root, "demo");
+        assert_eq!(names(&by_root_fn), vec!["run"]);
+    }
+
+    #[test]
+    fn crate_prefixed_qualifier_must_match_whole_path() {
+        let symbols = sample_symbols();
+        let root = Path::new(ROOT);
+        let full = EntrySpec::parse("crate::lsp::lsp_client::LspClient::initialize");
+        assert_eq!(matching_symbols(&full, &symbols, root, "demo").len(), 1);
+        let by_name = EntrySpec::parse("demo::lsp::lsp_client::LspClient::initialize");
+        assert_eq!(matching_symbols(&by_name, &symbols, root, "demo").len(), 1);
+        let partial = EntrySpec::parse("crate::LspClient::initialize");
+        assert!(matching_symbols(&partial, &symbols, root, "demo").is_empty());
+    }
+
+    #[test]
+    fn describe_candidate_shows_path_and_location() {
+        let symbols = sample_symbols();
+        assert_eq!(
+            describe_candidate(&symbols[1], Path::new(ROOT)),
+            "lsp::lsp_client::LspClient::new (src/lsp/lsp_client.rs:42)"
+        );
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn suggestions_rank_close_and_containing_names() {
+        let mut symbols = sample_symbols();
+        symbols.push(symbol(
+            "initialized",
+            "src/lsp/lsp_client.rs",
+            80,
+            Some("LspClient"),
+        ));
+        symbols.push(symbol("render", "src/app.rs", 30, None));
+        assert_eq!(
+            suggest_names("intialize", &symbols, 5),
+            vec!["initialize", "initialized"]
+        );
+        assert_eq!(suggest_names("nw", &symbols, 5), vec!["new"]);
+        assert_eq!(suggest_names("initial", &symbols, 1), vec!["initialize"]);
+        assert!(suggest_names("completely_unrelated", &symbols, 5).is_empty());
+    }
+
+    #[allow(deprecated)]
+    fn doc_symbol(
+        name: &str,
+        kind: SymbolKind,
+        lines: (u32, u32),
+        children: Option<Vec<DocumentSymbol>>,
+    ) -> DocumentSymbol {
+        let range = Range {
+            start: Position {
+                line: lines.0,
+                character: 0,
+            },
+            end: Position {
+                line: lines.1,
+                character: 1,
+            },
+        };
+        DocumentSymbol {
+            name: name.to_string(),
+            detail: None,
+            kind,
+            tags: None,
+            deprecated: None,
+            range,
+            selection_range: range,
+            children,
+        }
+    }
+
+    #[test]
+    fn function_containing_line_picks_innermost_function() {
+        let uri = Url::parse("file:///ws/src/app.rs").unwrap();
+        let symbols = vec![
+            doc_symbol("main", SymbolKind::FUNCTION, (0, 4), None),
+            doc_symbol(
+                "impl App",
+                SymbolKind::OBJECT,
+                (6, 30),
+                Some(vec![
+                    doc_symbol("new", SymbolKind::METHOD, (7, 10), None),
+                    doc_symbol("run", SymbolKind::METHOD, (12, 29), None),
+                ]),
+            ),
+        ];
+        let found = function_containing_line(&symbols, 13, &uri).unwrap();
+        assert_eq!(found.name, "run");
+        assert!(function_containing_line(&symbols, 5, &uri).is_none());
+        assert!(function_containing_line(&symbols, 6, &uri).is_none());
+    }
+
+    #[test]
+    fn functions_in_line_ranges_lists_each_touched_function_once() {
+        let uri = Url::parse("file:///ws/src/app.rs").unwrap();
+        let symbols = vec![
+            doc_symbol("main", SymbolKind::FUNCTION, (0, 4), None),
+            doc_symbol("helper", SymbolKind::FUNCTION, (6, 9), None),
+            doc_symbol("other", SymbolKind::FUNCTION, (11, 14), None),
+        ];
+        let found = functions_in_line_ranges(&symbols, &[(2, 3), (5, 8)], &uri);
+        let names: Vec<&str> = found.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["main", "helper"]);
+    }
+}
@@ -0,0 +1,158 @@
+//! Crates of functions defined outside the workspace.
+//!
+//! rust-analyzer points calls into the standard library at the sysroot sources
+//! (`.../lib/rustlib/src/rust/library/<crate>/src/...`), and calls into dependencies at the
+//! cargo registry (`.../registry/src/<index>/<name>-<version>/src/...`) or at a git checkout
+//! (`.../git/checkouts/<name>-<hash>/<revision>/src/...`, or `.../<revision>/<dir>/src/...`
+//! for a member of a multi-crate repository). [`crate_of`] reads the crate back from such a
+//! path so the builder can keep external callees as leaf nodes grouped by crate.
+
+use std::path::Path;
+
+/// Where an external crate comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalKind {
+    /// A crate of the standard library (`std`, `core`, `alloc`, ...) from the sysroot.
+    Std,
+    /// Any other crate outside the workspace.
+    Dependency,
+}
+
+/// The crate an external file belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExternalCrate {
+    /// Crate name as used in paths, with `-` replaced by `_`.
+    pub(crate) name: String,
+    pub(crate) kind: ExternalKind,
+    /// Path of the file relative to the crate root, e.g. `src/vec/mod.rs`.
+    pub(crate) file: String,
+}
+
+/// Finds the crate `path` belongs to: a standard library crate in the sysroot, a registry
+/// or git dependency, or else the directory holding the `src` directory the file is in.
+pub(crate) fn crate_of(path: &Path) -> Option<ExternalCrate> {
+    let parts: Vec<&str> = path
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect();
+    let external = |name: &str, kind: ExternalKind, rest: &[&str]| ExternalCrate {
+        name: name.replace('-', "_"),
+        kind,
+        file: rest.join("/"),
+    };
+
+    if let Some(i) = find_sequence(&parts, &["lib", "rustlib", "src", "rust", "library"]) {
+        let name = parts.get(i + 5)?;
+        return Some(external(name, ExternalKind::Std, &parts[i + 6..]));
+    }
+    if let Some(i) = find_sequence(&parts, &["registry", "src"]) {
+        let package = parts.get(i + 3)?;
+        let name = strip_version(package);
+        return Some(external(name, ExternalKind::Dependency, &parts[i + 4..]));
+    }
+    if let Some(i) = find_sequence(&parts, &["git", "checkouts"]) {
+        let checkout = parts.get(i + 2)?;
+        let rest = parts.get(i + 4..)?;
+        // A crate below the checkout root (a workspace member) is named by its directory.
+        return Some(match rest.iter().position(|part| *part == "src") {
+            Some(src) if src > 0 => external(rest[src - 1], ExternalKind::Dependency, &rest[src..]),
+            _ => {
+                let name = checkout
+                    .rsplit_once('-')
+                    .map_or(*checkout, |(name, _)| name);
+                external(name, ExternalKind::Dependency, rest)
+            }
+        });
+    }
+    let src = parts.iter().rposition(|part| *part == "src")?;
+    let name = parts.get(src.checked_sub(1)?)?;
+    Some(external(name, ExternalKind::Dependency, &parts[src..]))
+}
+
+/// Index of the first occurrence of `sequence` in `parts`.
+fn find_sequence(parts: &[&str], sequence: &[&str]) -> Option<usize> {
+    parts
+        .windows(sequence.len())
+        .position(|window| window == sequence)
+}
+
+/// Drops the `-<version>` suffix of a registry package directory: `serde_json-1.0.108`
+/// gives `serde_json`, `tokio-util-0.7.10` gives `tokio-util`.
+fn strip_version(package: &str) -> &str {
+    package
+        .match_indices('-')
+        .map(|(i, _)| i)
+        .find(|&i| looks_like_version(&package[i + 1..]))
+        .map_or(package, |i| &package[..i])
+}
+
+/// `true` for `MAJOR.MINOR.PATCH...`.
+fn looks_like_version(text: &str) -> bool {
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let mut parts = text.splitn(3, '.');
+    parts.next().is_some_and(is_number)
+        && parts.next().is_some_and(is_number)
+        && parts
+            .next()
+            .is_some_and(|patch| patch.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crate_at(path: &str) -> ExternalCrate {
+        crate_of(Path::new(path)).unwrap()
+    }
+
+    #[test]
+    fn sysroot_files_belong_to_std_crates() {
+        let vec = crate_at(
+            "/home/dev/.rustup/toolchains/stable-x86_64-unknown-linux-gnu/lib/rustlib/src/rust/library/alloc/src/vec/mod.rs",
+        );
+        assert_eq!(vec.name, "alloc");
+        assert_eq!(vec.kind, ExternalKind::Std);
+        assert_eq!(vec.file, "src/vec/mod.rs");
+    }
+
+    #[test]
+    fn registry_files_belong_to_their_package() {
+        let json = crate_at(
+            "/home/dev/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde_json-1.0.108/src/de.rs",
+        );
+        assert_eq!(json.name, "serde_json");
+        assert_eq!(json.kind, ExternalKind::Dependency);
+        assert_eq!(json.file, "src/de.rs");
+
+        let util = crate_at(
+            "/home/dev/.cargo/registry/src/index.crates.io-6f17d22bba15001f/tokio-util-0.7.10-rc.1/src/codec/mod.rs",
+        );
+        assert_eq!(util.name, "tokio_util");
+    }
+
+    #[test]
+    fn git_checkouts_and_path_dependencies_are_named_by_directory() {
+        let git = crate_at("/home/dev/.cargo/git/checkouts/lsp-types-1a2b3c4d/abc1234/src/lib.rs");
+        assert_eq!(git.name, "lsp_types");
+        assert_eq!(git.file, "src/lib.rs");
+
+        let member = crate_at(
+            "/home/dev/.cargo/git/checkouts/tower-lsp-9f8e7d6c/abc1234/crates/tower-lsp-macros/src/lib.rs",
+        );
+        assert_eq!(member.name, "tower_lsp_macros");
+        assert_eq!(member.file, "src/lib.rs");
+
+        let local = crate_at("/home/dev/shared/common/src/util/mod.rs");
+        assert_eq!(local.name, "common");
+        assert_eq!(local.file, "src/util/mod.rs");
+        assert!(crate_of(Path::new("/usr/include/stdio.h")).is_none());
+    }
+
+    #[test]
+    fn version_suffix_is_stripped() {
+        assert_eq!(strip_version("serde-1.0.195"), "serde");
+        assert_eq!(strip_version("h2-0.4.2"), "h2");
+        assert_eq!(strip_version("base64-2d-0.1.0"), "base64-2d");
+        assert_eq!(strip_version("unversioned"), "unversioned");
+    }
+}
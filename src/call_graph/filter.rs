@@ -0,0 +1,298 @@
+//! Include/exclude rules that decide which functions appear in a call graph.
+//!
+//! A [`NodeFilter`] is a list of include rules and a list of exclude rules. Each [`Rule`]
+//! matches a [`CallGraphNode`] by one of:
+//! - a glob on the defining file, relative to the workspace root (`src/lsp/**`)
+//! - a regex on the qualified label (`MyStruct::method`, see `meta_resolver`)
+//! - an exact group name
+//!
+//! A node is allowed when it matches no exclude rule and, if any include rules exist, at
+//! least one of them. The builder applies the filter during traversal so excluded functions
+//! are never expanded; [`remove_and_reconnect`] applies it to a finished graph instead,
+//! keeping the call chains that ran through removed functions.
+
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode, CallSite};
+use crate::error::FilterError;
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// One way of matching a node.
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// Glob on the workspace-relative file path. `*` does not cross `/`; use `**` for that.
+    File(GlobMatcher),
+    /// Regex searched for in the qualified label.
+    Name(Regex),
+    /// Exact group name.
+    Group(String),
+}
+
+impl Rule {
+    pub fn file(pattern: &str) -> Result<Self, FilterError> {
+        GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map(|glob| Rule::File(glob.compile_matcher()))
+            .map_err(|e| FilterError::InvalidPattern {
+                kind: "file glob",
+                pattern: pattern.to_string(),
+                reason: e.to_string(),
+            })
+    }
+
+    pub fn name(pattern: &str) -> Result<Self, FilterError> {
+        Regex::new(pattern)
+            .map(Rule::Name)
+            .map_err(|e| FilterError::InvalidPattern {
+                kind: "name regex",
+                pattern: pattern.to_string(),
+                reason: e.to_string(),
+            })
+    }
+
+    pub fn group(name: &str) -> Self {
+        Rule::Group(name.to_string())
+    }
+
+    /// Nodes without a known location never match a [`Rule::File`].
+    pub fn matches(&self, node: &CallGraphNode) -> bool {
+        match self {
+            Rule::File(glob) => node
+                .location
+                .as_ref()
+                .is_some_and(|location| glob.is_match(&location.file)),
+            Rule::Name(regex) => regex.is_match(&node.label),
+            Rule::Group(group) => node.group == *group,
+        }
+    }
+}
+
+/// Include and exclude rules. The default filter allows everything.
+#[derive(Debug, Clone, Default)]
+pub struct NodeFilter {
+    pub include: Vec<Rule>,
+    pub exclude: Vec<Rule>,
+}
+
+impl NodeFilter {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn allows(&self, node: &CallGraphNode) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|r| r.matches(node));
+        included && !self.exclude.iter().any(|r| r.matches(node))
+    }
+}
+
+/// Drops every node `filter` rejects, connecting its callers directly to its callees.
+///
+/// A kept caller gains an edge to every kept function it reaches through a chain of removed
+/// functions. The new edge carries the call sites of the first hop, which are the ones
+/// inside the caller; when a direct edge already exists, those sites are merged into it.
+/// Only an edge that stays a single dispatch edge keeps its [`dispatch`] flag.
+///
+/// [`dispatch`]: crate::call_graph::CallGraphEdge::dispatch
+pub fn remove_and_reconnect(graph: CallGraph, filter: &NodeFilter) -> CallGraph {
+    let (nodes, removed): (Vec<_>, Vec<_>) =
+        graph.nodes.into_iter().partition(|n| filter.allows(n));
+    let removed: HashSet<String> = removed.into_iter().map(|n| n.id).collect();
+
+    let mut callees: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        callees.entry(&edge.from).or_default().push(&edge.to);
+    }
+
+    let mut edges: BTreeMap<(String, String), BTreeSet<CallSite>> = BTreeMap::new();
+    let mut written: HashSet<(String, String)> = HashSet::new();
+    for edge in &graph.edges {
+        if removed.contains(&edge.from) {
+            continue;
+        }
+        let reconnected = removed.contains(&edge.to);
+        let targets = if reconnected {
+            kept_beyond(&edge.to, &callees, &removed)
+        } else {
+            vec![edge.to.as_str()]
+        };
+        for target in targets {
+            let pair = (edge.from.clone(), target.to_string());
+            if !edge.dispatch || reconnected {
+                written.insert(pair.clone());
+            }
+            edges
+                .entry(pair)
+                .or_default()
+                .extend(edge.call_sites.iter().cloned());
+        }
+    }
+
+    CallGraph {
+        nodes,
+        edges: edges
+            .into_iter()
+            .map(|((from, to), sites)| CallGraphEdge {
+                dispatch: !written.contains(&(from.clone(), to.clone())),
+                from,
+                to,
+                call_sites: sites.into_iter().collect(),
+                highlight: None,
+            })
+            .collect(),
+    }
+}
+
+/// Kept nodes reachable from the removed node `start` through removed nodes only.
+fn kept_beyond<'g>(
+    start: &'g str,
+    callees: &HashMap<&'g str, Vec<&'g str>>,
+    removed: &HashSet<String>,
+) -> Vec<&'g str> {
+    let mut seen: HashSet<&str> = HashSet::from([start]);
+    let mut stack = vec![start];
+    let mut kept = Vec::new();
+    while let Some(current) = stack.pop() {
+        for &next in callees.get(current).into_iter().flatten() {
+            if !seen.insert(next) {
+                continue;
+            }
+            if removed.contains(next) {
+                stack.push(next);
+            } else {
+                kept.push(next);
+            }
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{self, location};
+
+    fn node(id: &str, label: &str, group: &str, file: &str) -> CallGraphNode {
+        CallGraphNode {
+            location: location(file, 1, 1),
+            ..testing::node(id, label, group)
+        }
+    }
+
+    fn edge(from: &str, to: &str, line: u32) -> CallGraphEdge {
+        CallGraphEdge {
+            call_sites: vec![CallSite {
+                file: "src/main.rs".to_string(),
+                line,
+                column: 1,
+            }],
+            ..testing::edge(from, to)
+        }
+    }
+
+    fn edge_pairs(graph: &CallGraph) -> Vec<(&str, &str)> {
+        graph
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect()
+    }
+
+    /// a -> log -> fmt -> b, and a -> c
+    fn chain_graph() -> CallGraph {
+        CallGraph {
+            nodes: vec![
+                node("a", "main", "functions", "src/main.rs"),
+                node("log", "log_line", "functions", "src/util/log.rs"),
+                node("fmt", "Point::fmt", "Point", "src/util/fmt.rs"),
+                node("b", "App::run", "App", "src/app.rs"),
+                node("c", "helper", "functions", "src/main.rs"),
+            ],
+            edges: vec![
+                edge("a", "log", 3),
+                edge("log", "fmt", 10),
+                edge("fmt", "b", 20),
+                edge("a", "c", 4),
+            ],
+        }
+    }
+
+    #[test]
+    fn file_glob_does_not_cross_directories_with_single_star() {
+        let n = node("x", "x", "g", "src/util/log.rs");
+        assert!(!Rule::file("src/*.rs").unwrap().matches(&n));
+        assert!(Rule::file("src/**/*.rs").unwrap().matches(&n));
+        assert!(Rule::file("src/util/*").unwrap().matches(&n));
+    }
+
+    #[test]
+    fn name_regex_searches_qualified_label() {
+        let n = node("x", "Point::fmt", "Point", "src/a.rs");
+        assert!(Rule::name("::fmt$").unwrap().matches(&n));
+        assert!(!Rule::name("^fmt$").unwrap().matches(&n));
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported() {
+        let err = Rule::name("(").unwrap_err().to_string();
+        assert!(err.contains("name regex"), "got: {err}");
+        assert!(Rule::file("src/[").is_err());
+    }
+
+    #[test]
+    fn exclude_wins_over_include() {
+        let filter = NodeFilter {
+            include: vec![Rule::file("src/**").unwrap()],
+            exclude: vec![Rule::group("Point")],
+        };
+        assert!(filter.allows(&node("a", "main", "functions", "src/main.rs")));
+        assert!(!filter.allows(&node("f", "Point::fmt", "Point", "src/p.rs")));
+        assert!(!filter.allows(&node("t", "t", "functions", "tests/it.rs")));
+    }
+
+    #[test]
+    fn remove_and_reconnect_bridges_removed_chains() {
+        let filter = NodeFilter {
+            include: vec![],
+            exclude: vec![Rule::file("src/util/**").unwrap()],
+        };
+        let graph = remove_and_reconnect(chain_graph(), &filter);
+        assert_eq!(edge_pairs(&graph), vec![("a", "b"), ("a", "c")]);
+        // The bridged edge keeps the call site inside the caller.
+        assert_eq!(graph.edges[0].call_sites[0].line, 3);
+    }
+
+    #[test]
+    fn remove_and_reconnect_merges_with_existing_direct_edge() {
+        let mut graph = chain_graph();
+        graph.edges.push(edge("a", "b", 5));
+        let filter = NodeFilter {
+            include: vec![],
+            exclude: vec![Rule::name("^log_line$").unwrap(), Rule::group("Point")],
+        };
+        let graph = remove_and_reconnect(graph, &filter);
+        assert_eq!(edge_pairs(&graph), vec![("a", "b"), ("a", "c")]);
+        assert_eq!(graph.edges[0].call_count(), 2);
+    }
+
+    #[test]
+    fn remove_and_reconnect_keeps_only_direct_dispatch_edges() {
+        let mut graph = chain_graph();
+        graph.edges[1].dispatch = true; // log -> fmt
+        graph.edges[3].dispatch = true; // a -> c
+        let filter = NodeFilter {
+            include: vec![],
+            exclude: vec![Rule::name("^log_line$").unwrap()],
+        };
+        let graph = remove_and_reconnect(graph, &filter);
+        let flags: Vec<(&str, &str, bool)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.dispatch))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![("a", "c", true), ("a", "fmt", false), ("fmt", "b", false)]
+        );
+    }
+}
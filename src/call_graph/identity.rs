@@ -0,0 +1,177 @@
+//! Stable node identities.
+//!
+//! A node id is the path of its function: crate name, module path, `impl` block and name,
+//! e.g. `gen_callgraph::lsp::lsp_client::LspClient::send`, or
+//! `gen_callgraph::call_graph::model::<Highlight as Display>::fmt` for a trait method.
+//! Unlike a source position it survives edits elsewhere in the file, so the same function
+//! keeps its id from one run to the next. The position is still recorded in the node's
+//! [`location`](crate::call_graph::CallGraphNode::location).
+//!
+//! Functions sharing a path (e.g. `new` in two inherent impls of one type, or functions in
+//! inline modules) get a `#n` suffix from [`duplicate_suffix`], numbered among every such
+//! function of the workspace rather than those in the graph, so that a function keeps its
+//! id when another one with its path becomes reachable.
+
+use crate::call_graph::meta_resolver::{self, ImplBlock};
+use crate::call_graph::CallGraphNode;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Path of the function `name` defined in the workspace-relative `file`, inside
+/// `impl_block` when it is a method.
+///
+/// Files outside `src/` (integration tests, examples) use their path without the `.rs`
+/// extension as module path: `tests/cli.rs` gives `tests::cli`.
+pub(crate) fn function_path(
+    crate_name: &str,
+    file: &str,
+    impl_block: Option<&ImplBlock>,
+    name: &str,
+) -> String {
+    let mut segments = vec![crate_name.to_string()];
+    match meta_resolver::module_segments(Path::new(file)) {
+        Some(modules) => segments.extend(modules),
+        None => segments.extend(file.trim_end_matches(".rs").split('/').map(String::from)),
+    }
+    match impl_block {
+        Some(ImplBlock {
+            self_type,
+            trait_name: Some(trait_name),
+        }) => segments.push(format!("<{} as {}>", self_type, trait_name)),
+        Some(ImplBlock { self_type, .. }) => segments.push(self_type.clone()),
+        None => {}
+    }
+    segments.push(name.to_string());
+    segments.join("::")
+}
+
+/// Suffix of the function with path `path` whose definition starts at `start_line`, when
+/// other workspace functions share its path: `#n` for the n-th of them in the file.
+///
+/// `siblings` holds the path and a line within the definition of every workspace function
+/// with the same name in the same file, the function itself included. `None` when no other
+/// sibling has its path.
+pub(crate) fn duplicate_suffix(
+    path: &str,
+    start_line: u32,
+    siblings: &[(String, u32)],
+) -> Option<String> {
+    let lines: Vec<u32> = siblings
+        .iter()
+        .filter(|(sibling, _)| sibling == path)
+        .map(|&(_, line)| line)
+        .collect();
+    if lines.len() < 2 {
+        return None;
+    }
+    let before = lines.iter().filter(|&&line| line < start_line).count();
+    Some(format!("#{}", before + 1))
+}
+
+/// Tells apart nodes that still share an id, because [`duplicate_suffix`] had no workspace
+/// symbols for them, by appending `#1`, `#2`, ... in order of their location.
+pub(crate) fn number_duplicates(nodes: &mut [CallGraphNode]) {
+    let mut by_id: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        by_id.entry(node.id.clone()).or_default().push(index);
+    }
+    for mut indices in by_id.into_values().filter(|indices| indices.len() > 1) {
+        indices.sort_by_key(|&i| {
+            nodes[i]
+                .location
+                .as_ref()
+                .map(|l| (l.file.clone(), l.start_line, l.end_line))
+        });
+        for (occurrence, index) in indices.into_iter().enumerate() {
+            nodes[index].id = format!("{}#{}", nodes[index].id, occurrence + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing;
+
+    fn impl_block(self_type: &str, trait_name: Option<&str>) -> ImplBlock {
+        ImplBlock {
+            self_type: self_type.to_string(),
+            trait_name: trait_name.map(String::from),
+        }
+    }
+
+    #[test]
+    fn free_function_path_has_crate_and_module() {
+        assert_eq!(
+            function_path("my_crate", "src/lsp/client.rs", None, "connect"),
+            "my_crate::lsp::client::connect"
+        );
+        assert_eq!(
+            function_path("my_crate", "src/main.rs", None, "main"),
+            "my_crate::main"
+        );
+    }
+
+    #[test]
+    fn method_path_has_impl_owner_and_trait() {
+        let inherent = impl_block("Point", None);
+        let display = impl_block("Point", Some("Display"));
+        assert_eq!(
+            function_path("my_crate", "src/point.rs", Some(&inherent), "new"),
+            "my_crate::point::Point::new"
+        );
+        assert_eq!(
+            function_path("my_crate", "src/point.rs", Some(&display), "fmt"),
+            "my_crate::point::<Point as Display>::fmt"
+        );
+    }
+
+    #[test]
+    fn trait_generic_arguments_tell_impls_apart() {
+        let from_u32 = impl_block("Id", Some("From<u32>"));
+        let from_str = impl_block("Id", Some("From<&str>"));
+        assert_ne!(
+            function_path("my_crate", "src/id.rs", Some(&from_u32), "from"),
+            function_path("my_crate", "src/id.rs", Some(&from_str), "from")
+        );
+    }
+
+    #[test]
+    fn files_outside_src_use_their_path() {
+        assert_eq!(
+            function_path("my_crate", "tests/cli.rs", None, "runs"),
+            "my_crate::tests::cli::runs"
+        );
+    }
+
+    #[test]
+    fn duplicate_suffix_numbers_among_workspace_functions() {
+        let siblings = vec![
+            ("p::Point::new".to_string(), 40),
+            ("p::Point::new".to_string(), 10),
+            ("p::tests::new".to_string(), 70),
+        ];
+        assert_eq!(
+            duplicate_suffix("p::Point::new", 38, &siblings).as_deref(),
+            Some("#2")
+        );
+        assert_eq!(
+            duplicate_suffix("p::Point::new", 9, &siblings).as_deref(),
+            Some("#1")
+        );
+        assert_eq!(duplicate_suffix("p::tests::new", 68, &siblings), None);
+    }
+
+    #[test]
+    fn duplicates_are_numbered_by_location() {
+        let node = |id: &str, line: u32| CallGraphNode {
+            id: id.to_string(),
+            location: testing::location("src/point.rs", line, line + 3),
+            ..Default::default()
+        };
+        let mut nodes = vec![node("p::new", 40), node("p::other", 1), node("p::new", 10)];
+        number_duplicates(&mut nodes);
+        let ids: Vec<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["p::new#2", "p::other", "p::new#1"]);
+    }
+}
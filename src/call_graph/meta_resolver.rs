@@ -159,6 +159,90 @@ fn extract_type_name(type_expr: &str) -> String {
 /// Infers the impl owner by reading the source file and finding the impl block.
 /// This is used when LSP server doesn't provide container_name or detail.
 fn infer_impl_owner_from_source(item: &CallHierarchyItem) -> Option<String> {
+    parse_impl_owner(&find_impl_header_in_source(item)?)
+}
+
+/// The `impl` block a method is defined in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ImplBlock {
+    /// Implementing type without generic arguments (e.g., "MyStruct")
+    pub(crate) self_type: String,
+    /// Implemented trait with its generic arguments (e.g., "From<u32>"), `None` for inherent impls
+    pub(crate) trait_name: Option<String>,
+}
+
+/// Resolves the `impl` block `item` is defined in, from the detail field or else the
+/// source file. `None` for free functions.
+pub(crate) fn resolve_impl_block(item: &CallHierarchyItem) -> Option<ImplBlock> {
+    if let Some(after_impl) = item
+        .detail
+        .as_deref()
+        .and_then(|d| d.trim().strip_prefix("impl "))
+    {
+        return Some(ImplBlock {
+            self_type: parse_impl_target(after_impl)?,
+            trait_name: parse_impl_trait(after_impl),
+        });
+    }
+
+    let header = find_impl_header_in_source(item)?;
+    Some(ImplBlock {
+        self_type: parse_impl_owner(&header)?,
+        trait_name: parse_impl_trait(header.split_once("impl")?.1),
+    })
+}
+
+/// Parses the implemented trait from an impl declaration, dropping its module path but
+/// keeping its generic arguments.
+///
+/// Examples:
+/// - "fmt::Display for MyStruct" → "Display"
+/// - "<T> From<Vec<T>> for MyStruct<T>" → "From<Vec<T>>"
+/// - "MyStruct" → None (inherent impl)
+fn parse_impl_trait(impl_decl: &str) -> Option<String> {
+    let impl_decl = impl_decl.trim();
+    let impl_decl = if impl_decl.starts_with('<') {
+        skip_generic_params(impl_decl)
+    } else {
+        impl_decl
+    };
+
+    let (trait_path, _) = impl_decl.split_once(" for ")?;
+    let trait_path: String = trait_path.split_whitespace().collect::<Vec<_>>().join(" ");
+    let args_start = trait_path.find('<').unwrap_or(trait_path.len());
+    let name_start = trait_path[..args_start]
+        .rfind("::")
+        .map_or(0, |pos| pos + 2);
+    let trait_name = trait_path[name_start..].to_string();
+
+    if trait_name.is_empty() {
+        None
+    } else {
+        Some(trait_name)
+    }
+}
+
+/// Skips a leading `<...>` generic parameter list, including nested brackets.
+fn skip_generic_params(decl: &str) -> &str {
+    let mut depth = 0;
+    for (i, ch) in decl.char_indices() {
+        match ch {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return decl[i + 1..].trim_start();
+                }
+            }
+            _ => {}
+        }
+    }
+    decl
+}
+
+/// Reads the source file and returns the header of the innermost impl block enclosing
+/// `item`, joined into one line.
+fn find_impl_header_in_source(item: &CallHierarchyItem) -> Option<String> {
     let path = item.uri.to_file_path().ok()?;
     let text = std::fs::read_to_string(path).ok()?;
     let lines: Vec<&str> = text.lines().collect();
@@ -184,8 +268,8 @@ fn infer_impl_owner_from_source(item: &CallHierarchyItem) -> Option<String> {
             continue;
         }
 
-        if let Some(owner) = parse_impl_owner(&header) {
-            return Some(owner);
+        if parse_impl_owner(&header).is_some() {
+            return Some(header);
         }
     }
 
@@ -289,13 +373,21 @@ fn infer_module_owner_from_uri(
 ) -> Option<String> {
     let path = item.uri.to_file_path().ok()?;
     let rel = path.strip_prefix(workspace_root_path).ok()?;
+    let parts = module_segments(rel)?;
 
-    if rel == Path::new("src/main.rs") || rel == Path::new("src/lib.rs") {
+    if parts.is_empty() {
         return Some(crate_name.to_string());
     }
 
-    if !rel.starts_with("src") {
-        return None;
+    Some(parts.join("::"))
+}
+
+/// Module path segments of a workspace-relative source file under `src/`: `src/lsp/client.rs`
+/// and `src/lsp/client/mod.rs` give `["lsp", "client"]`; the crate root files (`src/main.rs`,
+/// `src/lib.rs`) give an empty path. `None` for files outside `src/`.
+pub(crate) fn module_segments(rel: &Path) -> Option<Vec<String>> {
+    if rel == Path::new("src/main.rs") || rel == Path::new("src/lib.rs") {
+        return Some(Vec::new());
     }
 
     let no_src = rel.strip_prefix("src").ok()?;
@@ -315,22 +407,152 @@ fn infer_module_owner_from_uri(
         }
     }
 
-    if parts.is_empty() {
-        return Some(crate_name.to_string());
+    Some(parts)
+}
+
+/// Returns `true` when `item` is defined in test code: any file under `tests/`, or a
+/// function nested inside a `#[cfg(test)]` inline module.
+pub(crate) fn is_in_test_code(item: &CallHierarchyItem, workspace_root_path: &Path) -> bool {
+    if workspace_relative_path(&item.uri, workspace_root_path).starts_with("tests/") {
+        return true;
     }
 
-    Some(parts.join("::"))
+    let Ok(path) = item.uri.to_file_path() else {
+        return false;
+    };
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.is_empty() {
+        return false;
+    }
+
+    let target_line = (item.selection_range.start.line as usize).min(lines.len() - 1);
+    let Some(fn_line) = find_nearest_fn_line(&lines, target_line, &item.name) else {
+        return false;
+    };
+    is_inside_cfg_test_module(&lines, fn_line)
+}
+
+/// Searches backwards from `fn_line` for an enclosing inline `mod` block whose attributes
+/// include `#[cfg(test)]`.
+fn is_inside_cfg_test_module(lines: &[&str], fn_line: usize) -> bool {
+    for start in (0..fn_line).rev() {
+        if !looks_like_inline_mod_start(lines[start]) {
+            continue;
+        }
+        // Only count braces before the fn line so the fn's own `{` does not keep a
+        // module that already closed looking open.
+        if !header_block_contains_line(lines, start, fn_line - 1) {
+            continue;
+        }
+
+        let has_cfg_test = lines[..start]
+            .iter()
+            .rev()
+            .map(|l| l.trim())
+            .take_while(|l| l.starts_with("#[") || l.starts_with("//"))
+            .any(|l| l.replace(' ', "") == "#[cfg(test)]");
+        if has_cfg_test {
+            return true;
+        }
+    }
+    false
+}
+
+fn looks_like_inline_mod_start(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let after_vis = if let Some(rest) = trimmed.strip_prefix("pub") {
+        // Skip `pub`, `pub(crate)`, `pub(super)` ...
+        match rest.find(' ') {
+            Some(pos) => rest[pos..].trim_start(),
+            None => return false,
+        }
+    } else {
+        trimmed
+    };
+    after_vis.starts_with("mod ") && line.contains('{')
+}
+
+/// Returns `true` when `item` is declared inside a `trait` block, so that a call to it may
+/// dispatch to any implementation.
+pub(crate) fn is_trait_method(item: &CallHierarchyItem) -> bool {
+    let Ok(path) = item.uri.to_file_path() else {
+        return false;
+    };
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.is_empty() {
+        return false;
+    }
+
+    let target_line = (item.selection_range.start.line as usize).min(lines.len() - 1);
+    let Some(fn_line) = find_nearest_fn_line(&lines, target_line, &item.name) else {
+        return false;
+    };
+    is_inside_trait_block(&lines, fn_line)
+}
+
+/// Searches backwards from `fn_line` for the innermost enclosing `trait` or `impl` block
+/// and returns `true` when it is a `trait`.
+fn is_inside_trait_block(lines: &[&str], fn_line: usize) -> bool {
+    (0..fn_line)
+        .rev()
+        .find(|&start| {
+            (looks_like_trait_start(lines[start]) || looks_like_impl_header_start(lines[start]))
+                && header_block_contains_line(lines, start, fn_line - 1)
+        })
+        .is_some_and(|start| looks_like_trait_start(lines[start]))
+}
+
+fn looks_like_trait_start(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let after_vis = if let Some(rest) = trimmed.strip_prefix("pub") {
+        // Skip `pub`, `pub(crate)`, `pub(super)` ...
+        match rest.find(' ') {
+            Some(pos) => rest[pos..].trim_start(),
+            None => return false,
+        }
+    } else {
+        trimmed
+    };
+    let after_unsafe = after_vis.strip_prefix("unsafe ").unwrap_or(after_vis);
+    after_unsafe.starts_with("trait ")
+}
+
+/// Returns the path of `uri` relative to the workspace root, using `/` separators.
+/// Falls back to the full URI string when the file lies outside the workspace.
+pub(crate) fn workspace_relative_path(uri: &lsp_types::Url, workspace_root_path: &Path) -> String {
+    let Ok(path) = uri.to_file_path() else {
+        return uri.to_string();
+    };
+    match path.strip_prefix(workspace_root_path) {
+        Ok(rel) => rel
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => uri.to_string(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use lsp_types::{CallHierarchyItem, Location, Position, Range, SymbolInformation, SymbolKind, Url};
+    use lsp_types::{
+        CallHierarchyItem, Location, Position, Range, SymbolInformation, SymbolKind, Url,
+    };
 
     fn make_item(name: &str, uri: &str, line: u32, detail: Option<&str>) -> CallHierarchyItem {
         let url = Url::parse(uri).unwrap();
         let pos = Position { line, character: 0 };
-        let range = Range { start: pos, end: pos };
+        let range = Range {
+            start: pos,
+            end: pos,
+        };
         CallHierarchyItem {
             name: name.to_string(),
             kind: SymbolKind::FUNCTION,
@@ -354,7 +576,10 @@ mod tests {
             deprecated: None,
             location: Location {
                 uri: url,
-                range: Range { start: pos, end: pos },
+                range: Range {
+                    start: pos,
+                    end: pos,
+                },
             },
             container_name: container.map(|s| s.to_string()),
         }
@@ -425,7 +650,10 @@ mod tests {
 
     #[test]
     fn test_parse_impl_target_trait_impl() {
-        assert_eq!(parse_impl_target("Display for MyStruct").unwrap(), "MyStruct");
+        assert_eq!(
+            parse_impl_target("Display for MyStruct").unwrap(),
+            "MyStruct"
+        );
     }
 
     #[test]
@@ -447,12 +675,16 @@ mod tests {
 
     #[test]
     fn test_impl_header_start_unsafe_impl() {
-        assert!(looks_like_impl_header_start("unsafe impl Sync for MyStruct {}"));
+        assert!(looks_like_impl_header_start(
+            "unsafe impl Sync for MyStruct {}"
+        ));
     }
 
     #[test]
     fn test_impl_header_start_unsafe_generic_impl() {
-        assert!(looks_like_impl_header_start("unsafe impl<T> Send for MyStruct<T> {}"));
+        assert!(looks_like_impl_header_start(
+            "unsafe impl<T> Send for MyStruct<T> {}"
+        ));
     }
 
     #[test]
@@ -541,7 +773,46 @@ mod tests {
 
     #[test]
     fn test_parse_impl_owner_generic_struct() {
-        assert_eq!(parse_impl_owner("impl<T> MyStruct<T> {").unwrap(), "MyStruct");
+        assert_eq!(
+            parse_impl_owner("impl<T> MyStruct<T> {").unwrap(),
+            "MyStruct"
+        );
+    }
+
+    // --- parse_impl_trait / resolve_impl_block ---
+
+    #[test]
+    fn test_parse_impl_trait_drops_module_path() {
+        assert_eq!(
+            parse_impl_trait("fmt::Display for Point").as_deref(),
+            Some("Display")
+        );
+    }
+
+    #[test]
+    fn test_parse_impl_trait_keeps_generic_arguments() {
+        assert_eq!(
+            parse_impl_trait("<T: Into<String>> From<Vec<T>> for Wrapper<T> {").as_deref(),
+            Some("From<Vec<T>>")
+        );
+    }
+
+    #[test]
+    fn test_parse_impl_trait_returns_none_for_inherent_impl() {
+        assert!(parse_impl_trait("<T> MyStruct<T> {").is_none());
+    }
+
+    #[test]
+    fn test_resolve_impl_block_from_detail() {
+        let detail = Some("impl From<u32> for Id");
+        let item = make_item("from", "file:///workspace/src/a.rs", 0, detail);
+        assert_eq!(
+            resolve_impl_block(&item),
+            Some(ImplBlock {
+                self_type: "Id".to_string(),
+                trait_name: Some("From<u32>".to_string()),
+            })
+        );
     }
 
     // --- infer_module_owner_from_uri ---
@@ -588,12 +859,111 @@ mod tests {
         assert!(result.is_none());
     }
 
+    // --- workspace_relative_path ---
+
+    #[test]
+    fn test_relative_path_inside_workspace() {
+        let uri = Url::parse("file:///workspace/src/lsp/client.rs").unwrap();
+        assert_eq!(
+            workspace_relative_path(&uri, Path::new("/workspace")),
+            "src/lsp/client.rs"
+        );
+    }
+
+    #[test]
+    fn test_relative_path_outside_workspace_keeps_uri() {
+        let uri = Url::parse("file:///elsewhere/lib.rs").unwrap();
+        assert_eq!(
+            workspace_relative_path(&uri, Path::new("/workspace")),
+            "file:///elsewhere/lib.rs"
+        );
+    }
+
+    // --- is_inside_cfg_test_module ---
+
+    #[test]
+    fn test_cfg_test_module_contains_fn() {
+        let lines = vec![
+            "fn a() {}",
+            "#[cfg(test)]",
+            "mod tests {",
+            "    fn helper() {",
+            "    }",
+            "}",
+        ];
+        assert!(is_inside_cfg_test_module(&lines, 3));
+    }
+
+    #[test]
+    fn test_cfg_test_module_excludes_fn_after_module() {
+        let lines = vec!["#[cfg(test)]", "mod tests {", "}", "fn after() {", "}"];
+        assert!(!is_inside_cfg_test_module(&lines, 3));
+    }
+
+    #[test]
+    fn test_plain_module_is_not_test_code() {
+        let lines = vec!["pub(crate) mod inner {", "    fn helper() {", "    }", "}"];
+        assert!(!is_inside_cfg_test_module(&lines, 1));
+    }
+
+    #[test]
+    fn test_trait_block_contains_declared_method() {
+        let lines = vec![
+            "#[async_trait]",
+            "pub(crate) trait Transport: Send {",
+            "    async fn send(&mut self) -> Result<(), Error>;",
+            "    fn flush(&mut self) {",
+            "    }",
+            "}",
+        ];
+        assert!(is_inside_trait_block(&lines, 2));
+        assert!(is_inside_trait_block(&lines, 3));
+    }
+
+    #[test]
+    fn test_impl_method_is_not_in_trait_block() {
+        let lines = vec![
+            "trait Transport {",
+            "    fn send(&mut self);",
+            "}",
+            "impl Transport for Stdio {",
+            "    fn send(&mut self) {",
+            "    }",
+            "}",
+        ];
+        assert!(!is_inside_trait_block(&lines, 4));
+    }
+
+    #[test]
+    fn test_trait_start_accepts_visibility_and_unsafe() {
+        assert!(looks_like_trait_start("pub unsafe trait Raw {"));
+        assert!(looks_like_trait_start("    trait Inner {"));
+        assert!(!looks_like_trait_start("fn trait_like() {"));
+        assert!(!looks_like_trait_start("impl Trait for X {"));
+    }
+
+    #[test]
+    fn test_is_in_test_code_for_tests_dir() {
+        let item = make_item(
+            "it_works",
+            "file:///workspace/tests/integration.rs",
+            0,
+            None,
+        );
+        assert!(is_in_test_code(&item, Path::new("/workspace")));
+    }
+
     // --- resolve_function_meta ---
 
     #[test]
     fn test_resolve_meta_priority1_uses_container_name() {
         let item = make_item("my_method", "file:///workspace/src/foo.rs", 5, None);
-        let symbol = make_symbol("my_method", "file:///workspace/src/foo.rs", 5, Some("MyStruct"));
+        let symbol = make_symbol(
+            "my_method",
+            "file:///workspace/src/foo.rs",
+            5,
+            Some("MyStruct"),
+        );
         let meta = resolve_function_meta(&item, &[symbol], Path::new("/workspace"), "my_crate");
         assert_eq!(meta.qualified_label, "MyStruct::my_method");
         assert_eq!(meta.group, "MyStruct");
@@ -1,27 +1,144 @@
 //! Domain model types for the call graph. Pure data structures with no I/O or analysis logic.
+//!
+//! The types derive `serde` traits so that [`crate::graph_json`] can persist them; metadata
+//! fields default when absent so older JSON files keep loading.
+
+use serde::{Deserialize, Serialize};
 
 /// A single node in the call graph, representing one function or method.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CallGraphNode {
-    /// Unique stable identifier (e.g. fully-qualified symbol name or LSP URI + range).
+    /// Unique stable identifier: the function's path, e.g. `my_crate::lsp::LspClient::send`
+    /// or `my_crate::point::<Point as Display>::fmt`, followed by `#n` when several
+    /// workspace functions share that path (see `call_graph::identity`). It does not change
+    /// when the function moves within its file, so saved graphs can be matched against it.
     pub id: String,
     /// Human-readable display label shown in the rendered output.
     pub label: String,
     /// Logical group (e.g. crate or module name) used to cluster nodes in the DOT output.
     pub group: String,
+    /// Where the function is defined, when known.
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+    /// Whether this is a free function or a method.
+    #[serde(default)]
+    pub kind: FunctionKind,
+    /// Raw `detail` reported by the language server (for rust-analyzer, the signature).
+    #[serde(default)]
+    pub detail: Option<String>,
+    /// `true` when the language server tagged the function as deprecated.
+    #[serde(default)]
+    pub deprecated: bool,
+    /// `true` when the function lives in test code (a `#[cfg(test)]` module or `tests/`).
+    #[serde(default)]
+    pub is_test: bool,
+    /// `true` when traversal stopped at this node because of a depth limit even though it
+    /// makes further calls within the workspace.
+    #[serde(default)]
+    pub truncated: bool,
+    /// `true` for the entry functions traversal started from.
+    #[serde(default)]
+    pub is_root: bool,
+    /// `true` for a function outside the workspace (the standard library or a dependency).
+    /// Its group is its crate, its location an absolute path, and its calls are never followed.
+    #[serde(default)]
+    pub external: bool,
+    /// Set by analyses to make the node stand out in the rendered output.
+    #[serde(default)]
+    pub highlight: Option<Highlight>,
+    /// Display size relative to the largest node, from `0.0` to `1.0`, set by analyses
+    /// that rank nodes. Renderers that support it draw larger nodes for larger values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<f64>,
+}
+
+impl CallGraphNode {
+    /// The bare function/method name: the last `::` segment of [`label`](Self::label).
+    ///
+    /// Renderers that already show the container/module as a cluster use this to avoid
+    /// repeating it on every node.
+    pub fn short_label(&self) -> &str {
+        self.label.rsplit("::").next().unwrap_or(&self.label)
+    }
+}
+
+/// The kind of callable a [`CallGraphNode`] represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionKind {
+    #[default]
+    Function,
+    Method,
+}
+
+/// Why a node or edge stands out in the rendered output. Renderers pick the styling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Highlight {
+    /// Part of a recursion cycle.
+    Cycle,
+    /// Not reachable from any chosen entry point (dead code).
+    Unreached,
+    /// A call that breaks an architecture layering rule.
+    Violation,
+    /// Present only in the newer of two compared snapshots.
+    Added,
+    /// Present only in the older of two compared snapshots.
+    Removed,
+    /// Touched by the change a change-impact analysis starts from.
+    Changed,
+}
+
+/// Source span of a function definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// Path of the defining file, relative to the workspace root when possible.
+    pub file: String,
+    /// 1-based first line of the definition.
+    pub start_line: u32,
+    /// 1-based last line of the definition.
+    pub end_line: u32,
+}
+
+/// A single place in the source where one function calls another.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CallSite {
+    /// Path of the file containing the call, relative to the workspace root when possible.
+    pub file: String,
+    /// 1-based line of the call expression.
+    pub line: u32,
+    /// 1-based column of the call expression.
+    pub column: u32,
 }
 
 /// A directed call edge: `from` calls `to`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CallGraphEdge {
     /// `id` of the calling node.
     pub from: String,
     /// `id` of the callee node.
     pub to: String,
+    /// Every distinct location in `from` where `to` is called, sorted by position.
+    #[serde(default)]
+    pub call_sites: Vec<CallSite>,
+    /// Set by analyses to make the edge stand out in the rendered output.
+    #[serde(default)]
+    pub highlight: Option<Highlight>,
+    /// `true` when `from` is a trait method and `to` an implementation a call to it may
+    /// dispatch to, rather than a call written in the source.
+    #[serde(default)]
+    pub dispatch: bool,
+}
+
+impl CallGraphEdge {
+    /// Number of distinct call sites behind this edge.
+    pub fn call_count(&self) -> usize {
+        self.call_sites.len()
+    }
 }
 
 /// The complete call graph: a collection of nodes and directed edges.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallGraph {
     pub nodes: Vec<CallGraphNode>,
     pub edges: Vec<CallGraphEdge>,
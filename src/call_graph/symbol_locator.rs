@@ -2,28 +2,58 @@
 //!
 //! # Primary entry points
 //!
-//! - [`find_function_symbol_with_retry`]: queries a single symbol by name with retry logic.
+//! - [`find_entry_symbol_with_retry`]: resolves one entry spec (bare name, qualified path or
+//!   `file:line`, see [`entry_spec`](super::entry_spec)) with retry logic.
 //!   rust-analyzer may not have finished indexing the workspace when first queried;
-//!   this function retries at fixed intervals to tolerate that delay.
+//!   this function retries at fixed intervals to tolerate that delay. A name that matches
+//!   several workspace functions is an error listing the candidates.
 //! - [`find_all_workspace_functions`]: full workspace scan used as a fallback when no
 //!   specific entry function is given.
+//! - [`find_changed_functions`]: the functions a diff touches, for change-impact analysis.
+//! - [`find_test_functions`]: the `#[test]` functions of `src/` and `tests/`.
 
+use crate::call_graph::entry_spec::{self, EntrySpec};
 use crate::error::{CallGraphError, SymbolError};
+use crate::git_diff::FileChange;
 use crate::lsp;
 use lsp_types::{SymbolInformation, SymbolKind};
 use std::path::PathBuf;
 use std::time::Duration;
 use tokio::time::sleep;
 
-pub(crate) async fn find_function_symbol_with_retry(
+/// Number of close names listed when an entry function is not found.
+const MAX_SUGGESTIONS: usize = 5;
+
+pub(crate) async fn find_entry_symbol_with_retry(
     client: &mut lsp::LspClient,
-    query: &str,
+    spec: &str,
     max_attempts: usize,
     interval: Duration,
-) -> Result<Option<SymbolInformation>, CallGraphError> {
+) -> Result<SymbolInformation, CallGraphError> {
+    let parsed = EntrySpec::parse(spec);
+    let not_found = |suggestions| SymbolError::EntryFunctionNotFound {
+        name: spec.to_string(),
+        suggestions,
+    };
+    if let EntrySpec::Location { file, line } = &parsed {
+        return find_function_at_location(client, file, *line)
+            .await?
+            .ok_or_else(|| not_found(Vec::new()).into());
+    }
+    let name = parsed.name().unwrap_or(spec);
+
+    // Once the server has reported that indexing finished, a missing name will not appear
+    // later, so a single attempt is enough.
+    let max_attempts = if client.is_indexing_complete() {
+        1
+    } else {
+        max_attempts
+    };
+    let mut symbols = Vec::new();
     for attempt in 1..=max_attempts {
-        if let Some(symbol) = find_function_symbol(client, query).await? {
-            return Ok(Some(symbol));
+        symbols = fetch_workspace_functions(client, name).await?;
+        if let Some(symbol) = select_entry_symbol(client, spec, &parsed, &symbols)? {
+            return Ok(symbol);
         }
 
         if attempt < max_attempts {
@@ -33,76 +63,125 @@ pub(crate) async fn find_function_symbol_with_retry(
 
     // Fallback: Try to find the function using textDocument/documentSymbol
     // This is more reliable for entry functions like 'main'
-    if let Some(symbol) = find_function_via_document_symbol(client, query).await? {
-        return Ok(Some(symbol));
+    if let EntrySpec::Name(name) = &parsed {
+        if let Some(symbol) = find_function_via_document_symbol(client, name).await? {
+            return Ok(symbol);
+        }
     }
 
-    Ok(None)
+    Err(not_found(entry_spec::suggest_names(name, &symbols, MAX_SUGGESTIONS)).into())
 }
 
 // Removed: workspace_function_symbols is no longer needed.
 // We now rely on LSP server to provide all necessary metadata in CallHierarchyItem.
 
-async fn find_function_symbol(
+/// In-workspace symbols for a lookup of `name`.
+async fn fetch_workspace_functions(
     client: &mut lsp::LspClient,
-    query: &str,
-) -> Result<Option<SymbolInformation>, CallGraphError> {
-    // Try exact query first
-    let symbols = client.workspace_symbol(query).await?;
-
-    // If exact query returns nothing or very few results, try empty query to get all symbols
-    let all_symbols = if symbols.len() < 5 {
-        client.workspace_symbol("").await?
-    } else {
-        symbols.clone()
-    };
+    name: &str,
+) -> Result<Vec<SymbolInformation>, CallGraphError> {
+    // Query by name first; rust-analyzer caps the number of results, so the empty query
+    // alone may miss the function in larger workspaces.
+    let mut all_symbols = client.workspace_symbol(name).await?;
+    if all_symbols.len() < 5 {
+        all_symbols.extend(client.workspace_symbol("").await?);
+    }
+    all_symbols.retain(|s| client.is_uri_in_workspace(&s.location.uri));
+    all_symbols.dedup_by(|a, b| a.name == b.name && a.location == b.location);
+    Ok(all_symbols)
+}
 
+/// The single function among `all_symbols` that `parsed` names, if any.
+fn select_entry_symbol(
+    client: &lsp::LspClient,
+    spec: &str,
+    parsed: &EntrySpec,
+    all_symbols: &[SymbolInformation],
+) -> Result<Option<SymbolInformation>, CallGraphError> {
     // If an exact name match exists in workspace but is not a function/method, report it clearly.
-    if let Some(sym) = all_symbols
-        .iter()
-        .find(|s| s.name == query && client.is_uri_in_workspace(&s.location.uri))
-    {
-        if sym.kind != SymbolKind::FUNCTION && sym.kind != SymbolKind::METHOD {
-            return Err(SymbolError::NotAFunction {
-                name: sym.name.clone(),
-                kind: sym.kind,
+    if let EntrySpec::Name(name) = parsed {
+        if let Some(sym) = all_symbols.iter().find(|s| &s.name == name) {
+            if sym.kind != SymbolKind::FUNCTION && sym.kind != SymbolKind::METHOD {
+                return Err(SymbolError::NotAFunction {
+                    name: sym.name.clone(),
+                    kind: sym.kind,
+                }
+                .into());
             }
-            .into());
         }
     }
 
-    // First try: exact name match with correct kind and in workspace
-    let exact = all_symbols
-        .iter()
-        .find(|s| {
-            (s.kind == SymbolKind::FUNCTION || s.kind == SymbolKind::METHOD)
-                && s.name == query
-                && client.is_uri_in_workspace(&s.location.uri)
-        })
-        .cloned();
-    if exact.is_some() {
-        return Ok(exact);
+    let workspace_root = client.workspace_root_path();
+    let mut candidates =
+        entry_spec::matching_symbols(parsed, all_symbols, workspace_root, client.crate_name());
+    candidates.sort_by_key(|s| (s.location.uri.as_str(), s.location.range.start.line));
+    candidates.dedup_by_key(|s| (s.location.uri.as_str(), s.location.range.start.line));
+
+    match candidates.as_slice() {
+        [] => Ok(None),
+        [only] => Ok(Some((*only).clone())),
+        several => Err(SymbolError::AmbiguousEntryFunction {
+            name: spec.to_string(),
+            candidates: several
+                .iter()
+                .map(|s| entry_spec::describe_candidate(s, workspace_root))
+                .collect(),
+        }
+        .into()),
     }
+}
 
-    // Second try: exact name match with correct kind (ignore workspace check)
-    let exact_no_ws = all_symbols
-        .iter()
-        .find(|s| {
-            (s.kind == SymbolKind::FUNCTION || s.kind == SymbolKind::METHOD) && s.name == query
-        })
-        .cloned();
-    if exact_no_ws.is_some() {
-        return Ok(exact_no_ws);
-    }
+/// Resolves a `file:line` entry spec: the innermost function in `file` (relative to the
+/// workspace root) whose body contains the 1-based `line`.
+async fn find_function_at_location(
+    client: &mut lsp::LspClient,
+    file: &str,
+    line: u32,
+) -> anyhow::Result<Option<SymbolInformation>> {
+    let file_path = PathBuf::from(client.workspace_root_path()).join(file);
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| anyhow::anyhow!("cannot read entry file {:?}: {}", file_path, e))?;
+    let uri = lsp_types::Url::from_file_path(&file_path)
+        .map_err(|_| anyhow::anyhow!("cannot build a URI for {:?}", file_path))?;
+
+    client.text_document_did_open(&uri, "rust", content).await?;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let symbols = client.text_document_document_symbol(&uri).await?;
+
+    Ok(entry_spec::function_containing_line(
+        &symbols,
+        line.saturating_sub(1),
+        &uri,
+    ))
+}
 
-    let partial = symbols
-        .iter()
-        .find(|s| {
-            (s.kind == SymbolKind::FUNCTION || s.kind == SymbolKind::METHOD)
-                && client.is_uri_in_workspace(&s.location.uri)
-        })
-        .cloned();
-    Ok(partial)
+/// The functions whose definitions contain a changed line, found through the document
+/// symbols of each changed Rust file. Files that no longer exist are skipped.
+pub(crate) async fn find_changed_functions(
+    client: &mut lsp::LspClient,
+    changes: &[FileChange],
+) -> anyhow::Result<Vec<SymbolInformation>> {
+    let workspace_root = PathBuf::from(client.workspace_root_path());
+    let mut functions = Vec::new();
+    for change in changes.iter().filter(|c| c.path.ends_with(".rs")) {
+        let file_path = workspace_root.join(&change.path);
+        let Ok(content) = std::fs::read_to_string(&file_path) else {
+            continue;
+        };
+        let uri = lsp_types::Url::from_file_path(&file_path)
+            .map_err(|_| anyhow::anyhow!("cannot build a URI for {:?}", file_path))?;
+
+        client.text_document_did_open(&uri, "rust", content).await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let symbols = client.text_document_document_symbol(&uri).await?;
+
+        functions.extend(entry_spec::functions_in_line_ranges(
+            &symbols,
+            &change.ranges,
+            &uri,
+        ));
+    }
+    Ok(functions)
 }
 
 /// Fallback: Try to find a function by opening common entry point files
@@ -211,7 +290,7 @@ pub(crate) async fn find_all_workspace_functions(
     }
 
     let rust_files = collect_rust_files(&src_dir);
-    println!(
+    eprintln!(
         "  Scanning {} source files for function symbols...",
         rust_files.len()
     );
@@ -246,6 +325,72 @@ pub(crate) async fn find_all_workspace_functions(
     Ok(all_symbols)
 }
 
+/// Collects the `#[test]` functions (including `#[tokio::test]` and similar) of every Rust
+/// source file under `src/` and `tests/`.
+pub(crate) async fn find_test_functions(
+    client: &mut lsp::LspClient,
+) -> anyhow::Result<Vec<SymbolInformation>> {
+    let workspace_root = PathBuf::from(client.workspace_root_path());
+    let mut tests = Vec::new();
+    for dir in ["src", "tests"] {
+        for file_path in collect_rust_files(&workspace_root.join(dir)) {
+            let Ok(content) = std::fs::read_to_string(&file_path) else {
+                continue;
+            };
+            // Most files have no tests; skip them without asking the server.
+            if !content.contains("test") {
+                continue;
+            }
+            let Ok(uri) = lsp_types::Url::from_file_path(&file_path) else {
+                continue;
+            };
+
+            client
+                .text_document_did_open(&uri, "rust", content.clone())
+                .await?;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let doc_symbols = client.text_document_document_symbol(&uri).await?;
+
+            let mut functions = Vec::new();
+            collect_function_symbols_from_doc(&doc_symbols, &uri, &mut functions);
+            let lines: Vec<&str> = content.lines().collect();
+            tests.extend(
+                functions
+                    .into_iter()
+                    .filter(|f| has_test_attribute(&lines, f.location.range.start.line as usize)),
+            );
+        }
+    }
+    Ok(tests)
+}
+
+/// Returns `true` when the function declared on `fn_line` carries a test attribute:
+/// `#[test]` or one whose path ends in `::test`, such as `#[tokio::test]`.
+fn has_test_attribute(lines: &[&str], fn_line: usize) -> bool {
+    let Some(declaration) = lines.get(fn_line) else {
+        return false;
+    };
+    // `#[test] fn name()` on one line.
+    let before_fn = declaration.split("fn ").next().unwrap_or_default();
+    let attributes = lines[..fn_line]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .take_while(|line| line.starts_with("#[") || line.starts_with("//"))
+        .filter(|line| line.starts_with("#["))
+        .chain(std::iter::once(before_fn.trim()));
+    attributes
+        .flat_map(|line| line.split("#[").skip(1))
+        .any(|attribute| {
+            let path = attribute
+                .split(['(', ']'])
+                .next()
+                .unwrap_or_default()
+                .trim();
+            path == "test" || path.ends_with("::test")
+        })
+}
+
 /// Recursively walks `dir` and returns all `.rs` file paths.
 fn collect_rust_files(dir: &std::path::Path) -> Vec<PathBuf> {
     let mut files = Vec::new();
@@ -447,4 +592,37 @@ mod tests {
         collect_function_symbols_from_doc(&symbols, &uri, &mut out);
         assert_eq!(out[0].location.uri, uri);
     }
+
+    // --- has_test_attribute ---
+
+    #[test]
+    fn test_has_test_attribute_recognizes_test_attributes() {
+        let lines = vec![
+            "#[test]",
+            "fn plain() {}",
+            "#[tokio::test(flavor = \"multi_thread\")]",
+            "/// Doc comment",
+            "async fn async_test() {}",
+            "#[test] fn one_line() {}",
+        ];
+        assert!(has_test_attribute(&lines, 1));
+        assert!(has_test_attribute(&lines, 4));
+        assert!(has_test_attribute(&lines, 5));
+    }
+
+    #[test]
+    fn test_has_test_attribute_rejects_other_functions() {
+        let lines = vec![
+            "#[test]",
+            "fn plain() {}",
+            "fn helper() {}",
+            "#[cfg(test)]",
+            "fn cfg_only() {}",
+            "#[test_case(1)]",
+            "fn parametrized(n: u32) {}",
+        ];
+        assert!(!has_test_attribute(&lines, 2));
+        assert!(!has_test_attribute(&lines, 4));
+        assert!(!has_test_attribute(&lines, 6));
+    }
 }
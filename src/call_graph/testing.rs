@@ -0,0 +1,39 @@
+//! Graph fixtures shared by unit tests.
+
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode, SourceLocation};
+
+/// Node `id` labelled `label` in `group`, with default metadata.
+pub(crate) fn node(id: &str, label: &str, group: &str) -> CallGraphNode {
+    CallGraphNode {
+        id: id.to_string(),
+        label: label.to_string(),
+        group: group.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Location of a definition spanning `start_line..=end_line` of `file`.
+pub(crate) fn location(file: &str, start_line: u32, end_line: u32) -> Option<SourceLocation> {
+    Some(SourceLocation {
+        file: file.to_string(),
+        start_line,
+        end_line,
+    })
+}
+
+/// Call from `from` to `to` without call sites.
+pub(crate) fn edge(from: &str, to: &str) -> CallGraphEdge {
+    CallGraphEdge {
+        from: from.to_string(),
+        to: to.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Graph of `nodes` with a call for every `(from, to)` pair in `edges`.
+pub(crate) fn graph(nodes: Vec<CallGraphNode>, edges: &[(&str, &str)]) -> CallGraph {
+    CallGraph {
+        nodes,
+        edges: edges.iter().map(|&(from, to)| edge(from, to)).collect(),
+    }
+}
@@ -15,18 +15,45 @@
 //!
 //! # Traversal strategy
 //!
-//! Outgoing calls are explored via a depth-first traversal using an explicit stack
-//! (`Vec`). `visited_nodes` prevents re-expanding the same call hierarchy item;
-//! `visited_edges` deduplicates parallel call edges between the same pair of functions.
+//! Calls are explored via a breadth-first traversal using an explicit queue (`VecDeque`),
+//! so the depth at which an item is first reached is its shortest call distance from the
+//! roots. [`TraversalDirection`] selects whether each item is expanded through its outgoing
+//! calls (callees) or its incoming calls (callers); either way, edges always point from
+//! caller to callee. [`TraversalState`] holds what every traversal contributing to one graph
+//! shares: `visited_depths` records the shallowest depth each call hierarchy item was
+//! handled at, so an item is only handled again when it is reached closer to a root (as
+//! when the all-symbols traversal later starts from an item an earlier root reached at the
+//! depth limit); `visited_edges` deduplicates parallel call edges between the same pair of
+//! functions and accumulates the distinct call sites (`fromRanges`) reported for each of them.
+//!
+//! Neighbours rejected by [`TraversalOptions::filter`] are dropped before they become nodes,
+//! so nothing behind them is explored. When [`TraversalOptions::max_depth`] is set, items at
+//! that depth are not expanded. If they still have calls in the workspace they are marked
+//! `truncated` so renderers can tell them apart from genuine leaves.
+//!
+//! rust-analyzer reports a call through a trait object or generic bound as a call to the
+//! trait method declaration. With [`TraversalOptions::expand_dispatch`], expanding such a
+//! declaration also asks for its implementations and continues into them along dispatch
+//! edges.
+//!
+//! Callees outside the workspace are dropped unless [`TraversalOptions::external`] keeps
+//! their crate (see [`external::crate_of`]); kept ones become `external` leaf nodes grouped
+//! by crate, and are never expanded.
 
+use crate::call_graph::external::{self, ExternalKind};
+use crate::call_graph::filter::NodeFilter;
+use crate::call_graph::identity;
 use crate::call_graph::meta_resolver;
 use crate::call_graph::symbol_locator;
-use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode};
+use crate::call_graph::{
+    CallGraph, CallGraphEdge, CallGraphNode, CallSite, FunctionKind, SourceLocation,
+};
 use crate::error::{CallGraphError, SymbolError};
+use crate::git_diff::FileChange;
 use crate::lsp;
 
-use lsp_types::{CallHierarchyItem, SymbolInformation, SymbolKind};
-use std::collections::{HashMap, HashSet};
+use lsp_types::{CallHierarchyItem, Range, SymbolInformation, SymbolKind, SymbolTag, Url};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::time::Duration;
 
 /// Builds a call graph by driving LSP queries against an active session.
@@ -36,6 +63,99 @@ use std::time::Duration;
 /// responsibility of [`crate::lsp_session::LspSession`].
 pub struct CallGraphBuilder<'a> {
     client: &'a mut lsp::LspClient,
+    options: TraversalOptions,
+}
+
+/// Limits applied while traversing. The default explores everything reachable.
+#[derive(Debug, Clone, Default)]
+pub struct TraversalOptions {
+    /// Do not expand items more than this many calls away from the roots.
+    pub max_depth: Option<usize>,
+    /// Functions the filter rejects are left out of the graph and never expanded. Roots are
+    /// always kept.
+    pub filter: NodeFilter,
+    /// When following callees, continue from a trait method declaration to every
+    /// implementation of it, connected by a dispatch edge.
+    pub expand_dispatch: bool,
+    /// Callees outside the workspace to keep as leaf nodes.
+    pub external: ExternalCalls,
+}
+
+/// Which callees outside the workspace are kept as leaf nodes. By default none are.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExternalCalls {
+    /// Functions of the standard library crates (`std`, `core`, `alloc`, ...).
+    pub std: bool,
+    /// Functions of any other crate: registry, git and path dependencies.
+    pub dependencies: bool,
+}
+
+impl ExternalCalls {
+    fn keeps(&self, kind: ExternalKind) -> bool {
+        match kind {
+            ExternalKind::Std => self.std,
+            ExternalKind::Dependency => self.dependencies,
+        }
+    }
+}
+
+/// Distinct call sites collected per `(caller id, callee id)` pair during traversal.
+type EdgeSites = HashMap<(String, String), BTreeSet<CallSite>>;
+
+/// Which side of the call hierarchy [`traverse_items`] expands from each visited item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalDirection {
+    /// Follow `callHierarchy/outgoingCalls`: the graph shows everything the root calls.
+    Callees,
+    /// Follow `callHierarchy/incomingCalls`: the graph shows everything that calls the root.
+    Callers,
+}
+
+/// Mutable state shared by every [`traverse_items`] call that contributes to one graph.
+#[derive(Default)]
+struct TraversalState {
+    /// Shallowest depth each item was handled at.
+    visited_depths: HashMap<String, usize>,
+    visited_edges: EdgeSites,
+    /// Edges of `visited_edges` that lead from a trait method to an implementation.
+    dispatch_edges: HashSet<(String, String)>,
+    node_info: HashMap<String, CallGraphNode>,
+}
+
+impl TraversalState {
+    /// Records that `item_id`, already in `node_info`, is reached at `depth`. Returns `false`
+    /// when it was handled at this depth or a shallower one before, as handling it again
+    /// would add nothing. Otherwise clears its `truncated` flag: a depth limit cutting it
+    /// off at a deeper depth no longer applies.
+    fn visit(&mut self, item_id: &str, depth: usize) -> bool {
+        if !self.needs_visit(item_id, depth) {
+            return false;
+        }
+        self.visited_depths.insert(item_id.to_string(), depth);
+        if let Some(node) = self.node_info.get_mut(item_id) {
+            node.truncated = false;
+        }
+        true
+    }
+
+    /// `true` when `item_id` still has to be handled at `depth`.
+    fn needs_visit(&self, item_id: &str, depth: usize) -> bool {
+        self.visited_depths
+            .get(item_id)
+            .is_none_or(|&seen| seen > depth)
+    }
+
+    fn mark_roots(&mut self, root_ids: &[String]) {
+        for id in root_ids {
+            if let Some(node) = self.node_info.get_mut(id) {
+                node.is_root = true;
+            }
+        }
+    }
+
+    fn into_call_graph(self) -> CallGraph {
+        build_call_graph(self.node_info, self.visited_edges, &self.dispatch_edges)
+    }
 }
 
 /// Read-only context passed to [`traverse_items`] for resolving function metadata.
@@ -51,35 +171,129 @@ impl<'a> CallGraphBuilder<'a> {
     ///
     /// The session must already be initialized before calling this constructor.
     pub fn new(client: &'a mut lsp::LspClient) -> Self {
-        CallGraphBuilder { client }
+        CallGraphBuilder {
+            client,
+            options: TraversalOptions::default(),
+        }
+    }
+
+    /// Replaces the default (unlimited) [`TraversalOptions`].
+    pub fn with_options(mut self, options: TraversalOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Generates the merged call graph of everything `entries` call, transitively. The entry
+    /// functions are marked as roots.
+    pub async fn generate_call_graph(
+        &mut self,
+        entries: &[String],
+    ) -> Result<CallGraph, CallGraphError> {
+        self.collect_call_graph_from(entries, TraversalDirection::Callees)
+            .await
     }
 
-    pub async fn generate_call_graph(&mut self, entry: &str) -> Result<CallGraph, CallGraphError> {
-        self.collect_call_graph_from(entry).await
+    /// Generates the reverse call graph of `targets`: every function that transitively calls
+    /// one of them. Edges still point from caller to callee, so the targets (marked as roots)
+    /// are the only sinks.
+    pub async fn generate_caller_graph(
+        &mut self,
+        targets: &[String],
+    ) -> Result<CallGraph, CallGraphError> {
+        self.collect_call_graph_from(targets, TraversalDirection::Callers)
+            .await
     }
 
     pub async fn generate_call_graph_all(&mut self) -> Result<CallGraph, CallGraphError> {
-        self.collect_call_graph_all_symbols().await
+        self.collect_call_graph_all_symbols(&[]).await
     }
 
-    async fn collect_call_graph_from(&mut self, entry: &str) -> Result<CallGraph, CallGraphError> {
+    /// Generates the graph of every workspace function, with the `#[test]` functions of
+    /// `src/` and `tests/` marked as roots. Test files that `workspace/symbol` misses are
+    /// included as well.
+    pub async fn generate_test_graph(&mut self) -> Result<CallGraph, CallGraphError> {
+        let tests = symbol_locator::find_test_functions(self.client).await?;
+        eprintln!("Found {} test functions", tests.len());
+        self.collect_call_graph_all_symbols(&tests).await
+    }
+
+    /// Generates the reverse call graph of every function that `changes` touch: the changed
+    /// functions (marked as roots) and everything that transitively calls them. Empty when
+    /// the changes touch no function.
+    pub async fn generate_impact_graph(
+        &mut self,
+        changes: &[FileChange],
+    ) -> Result<CallGraph, CallGraphError> {
+        let changed = symbol_locator::find_changed_functions(self.client, changes).await?;
+        let mut roots = Vec::new();
+        for symbol in &changed {
+            roots.extend(
+                self.client
+                    .text_document_prepare_call_hierarchy(symbol)
+                    .await?,
+            );
+        }
+        self.traverse_from_roots(roots, TraversalDirection::Callers)
+            .await
+    }
+
+    async fn collect_call_graph_from(
+        &mut self,
+        entries: &[String],
+        direction: TraversalDirection,
+    ) -> Result<CallGraph, CallGraphError> {
+        let mut roots = Vec::new();
+        for entry in entries {
+            roots.extend(self.prepare_roots(entry).await?);
+        }
+        self.traverse_from_roots(roots, direction).await
+    }
+
+    /// Traverses from `roots` in `direction` into one graph with the roots marked.
+    async fn traverse_from_roots(
+        &mut self,
+        roots: Vec<CallHierarchyItem>,
+        direction: TraversalDirection,
+    ) -> Result<CallGraph, CallGraphError> {
         let function_symbols = self.client.workspace_symbol("").await?;
         let workspace_root_path = self.client.workspace_root_path().to_path_buf();
         let crate_name = self.client.crate_name().to_string();
 
-        let Some(symbol) = symbol_locator::find_function_symbol_with_retry(
+        let mut state = TraversalState::default();
+
+        let meta_ctx = MetaContext {
+            function_symbols: &function_symbols,
+            workspace_root_path: &workspace_root_path,
+            crate_name: &crate_name,
+        };
+
+        let root_ids: Vec<String> = roots.iter().map(call_item_key).collect();
+        traverse_items(
+            self.client,
+            roots,
+            direction,
+            &self.options,
+            &meta_ctx,
+            &mut state,
+        )
+        .await?;
+        state.mark_roots(&root_ids);
+
+        Ok(state.into_call_graph())
+    }
+
+    /// Resolves `entry` to the call hierarchy items traversal starts from.
+    async fn prepare_roots(
+        &mut self,
+        entry: &str,
+    ) -> Result<Vec<CallHierarchyItem>, CallGraphError> {
+        let symbol = symbol_locator::find_entry_symbol_with_retry(
             self.client,
             entry,
             20,
             Duration::from_millis(500),
         )
-        .await?
-        else {
-            return Err(SymbolError::EntryFunctionNotFound {
-                name: entry.to_string(),
-            }
-            .into());
-        };
+        .await?;
 
         let roots = self
             .client
@@ -91,31 +305,15 @@ impl<'a> CallGraphBuilder<'a> {
             }
             .into());
         }
-
-        let mut visited_nodes: HashSet<String> = HashSet::new();
-        let mut visited_edges: HashSet<(String, String)> = HashSet::new();
-        let mut node_info: HashMap<String, (String, String)> = HashMap::new();
-
-        let meta_ctx = MetaContext {
-            function_symbols: &function_symbols,
-            workspace_root_path: &workspace_root_path,
-            crate_name: &crate_name,
-        };
-
-        traverse_items(
-            self.client,
-            roots,
-            &meta_ctx,
-            &mut visited_nodes,
-            &mut visited_edges,
-            &mut node_info,
-        )
-        .await?;
-
-        Ok(build_call_graph(node_info, visited_edges))
+        Ok(roots)
     }
 
-    async fn collect_call_graph_all_symbols(&mut self) -> Result<CallGraph, CallGraphError> {
+    /// Traverses the callees of every workspace function the filter allows, and of `roots`,
+    /// which are marked as roots of the graph and kept regardless of the filter.
+    async fn collect_call_graph_all_symbols(
+        &mut self,
+        roots: &[SymbolInformation],
+    ) -> Result<CallGraph, CallGraphError> {
         let function_symbols = self.client.workspace_symbol("").await?;
         let workspace_root_path = self.client.workspace_root_path().to_path_buf();
         let crate_name = self.client.crate_name().to_string();
@@ -132,7 +330,7 @@ impl<'a> CallGraphBuilder<'a> {
 
         // Fallback: when workspace/symbol returns nothing, scan source files directly.
         if workspace_functions.is_empty() {
-            println!("  workspace/symbol returned no results, falling back to source file scan.");
+            eprintln!("  workspace/symbol returned no results, falling back to source file scan.");
             workspace_functions = symbol_locator::find_all_workspace_functions(self.client).await?;
         }
 
@@ -141,14 +339,12 @@ impl<'a> CallGraphBuilder<'a> {
                 "no function symbols found in workspace",
             ));
         }
-        println!(
+        eprintln!(
             "Found {} function symbols in workspace",
             workspace_functions.len()
         );
 
-        let mut visited_nodes: HashSet<String> = HashSet::new();
-        let mut visited_edges: HashSet<(String, String)> = HashSet::new();
-        let mut node_info: HashMap<String, (String, String)> = HashMap::new();
+        let mut state = TraversalState::default();
 
         let meta_ctx = MetaContext {
             function_symbols: &function_symbols,
@@ -156,27 +352,48 @@ impl<'a> CallGraphBuilder<'a> {
             crate_name: &crate_name,
         };
 
-        for symbol in &workspace_functions {
-            let roots = self
+        let mut root_ids = Vec::new();
+        for (index, symbol) in roots.iter().chain(&workspace_functions).enumerate() {
+            let mut items = self
                 .client
                 .text_document_prepare_call_hierarchy(symbol)
                 .await?;
+            if index < roots.len() {
+                root_ids.extend(items.iter().map(call_item_key));
+            } else {
+                items = allowed_items(items, &self.options.filter, &meta_ctx);
+            }
 
             traverse_items(
                 self.client,
-                roots,
+                items,
+                TraversalDirection::Callees,
+                &self.options,
                 &meta_ctx,
-                &mut visited_nodes,
-                &mut visited_edges,
-                &mut node_info,
+                &mut state,
             )
             .await?;
         }
+        state.mark_roots(&root_ids);
 
-        Ok(build_call_graph(node_info, visited_edges))
+        Ok(state.into_call_graph())
     }
 }
 
+/// The items of `items` whose nodes `filter` allows.
+fn allowed_items(
+    items: Vec<CallHierarchyItem>,
+    filter: &NodeFilter,
+    meta_ctx: &MetaContext<'_>,
+) -> Vec<CallHierarchyItem> {
+    items
+        .into_iter()
+        .filter(|item| filter.allows(&node_from_item(item, meta_ctx)))
+        .collect()
+}
+
+/// Identifies `item` during traversal. Call hierarchy items are keyed by position, which
+/// tells apart any two functions; [`build_call_graph`] replaces the keys by stable node ids.
 fn call_item_key(item: &CallHierarchyItem) -> String {
     format!(
         "{}:{}:{}:{}",
@@ -187,54 +404,87 @@ fn call_item_key(item: &CallHierarchyItem) -> String {
 async fn traverse_items(
     client: &mut lsp::LspClient,
     initial_items: Vec<CallHierarchyItem>,
+    direction: TraversalDirection,
+    options: &TraversalOptions,
     meta_ctx: &MetaContext<'_>,
-    visited_nodes: &mut HashSet<String>,
-    visited_edges: &mut HashSet<(String, String)>,
-    node_info: &mut HashMap<String, (String, String)>,
+    state: &mut TraversalState,
 ) -> Result<(), CallGraphError> {
-    let mut stack = initial_items;
+    let mut queue: VecDeque<(CallHierarchyItem, usize)> =
+        initial_items.into_iter().map(|item| (item, 0)).collect();
 
-    while let Some(item) = stack.pop() {
+    while let Some((item, depth)) = queue.pop_front() {
         if !client.is_uri_in_workspace(&item.uri) {
             continue;
         }
 
-        let from_id = call_item_key(&item);
-        let from_meta = meta_resolver::resolve_function_meta(
-            &item,
-            meta_ctx.function_symbols,
-            meta_ctx.workspace_root_path,
-            meta_ctx.crate_name,
-        );
-        node_info.insert(
-            from_id.clone(),
-            (from_meta.qualified_label, from_meta.group),
-        );
+        let item_id = call_item_key(&item);
+        state
+            .node_info
+            .entry(item_id.clone())
+            .or_insert_with(|| node_from_item(&item, meta_ctx));
 
-        if !visited_nodes.insert(from_id.clone()) {
+        if !state.visit(&item_id, depth) {
             continue;
         }
 
-        let outgoing = client.call_hierarchy_outgoing_calls(&item).await?;
+        let mut neighbours: Vec<(CallHierarchyItem, Vec<Range>, bool)> =
+            fetch_neighbours(client, &item, direction)
+                .await?
+                .into_iter()
+                .map(|(neighbour, from_ranges)| (neighbour, from_ranges, false))
+                .collect();
+        if options.expand_dispatch
+            && direction == TraversalDirection::Callees
+            && meta_resolver::is_trait_method(&item)
+        {
+            for implementation in fetch_implementations(client, &item).await? {
+                neighbours.push((implementation, Vec::new(), true));
+            }
+        }
 
-        for call in outgoing {
-            let child = call.to;
-            if !client.is_uri_in_workspace(&child.uri) {
-                continue;
+        if options.max_depth.is_some_and(|max| depth >= max) {
+            // Frontier item: leave it unexpanded, but remember whether that hides calls,
+            // implementations reached by dispatch included.
+            let truncated = neighbours.iter().any(|(neighbour, _, _)| {
+                neighbour_node(client, neighbour, direction, options, meta_ctx).is_some()
+            });
+            if let Some(node) = state.node_info.get_mut(&item_id) {
+                node.truncated = truncated;
             }
+            continue;
+        }
 
-            let to_id = call_item_key(&child);
-            let to_meta = meta_resolver::resolve_function_meta(
-                &child,
-                meta_ctx.function_symbols,
-                meta_ctx.workspace_root_path,
-                meta_ctx.crate_name,
-            );
-            node_info.insert(to_id.clone(), (to_meta.qualified_label, to_meta.group));
-            visited_edges.insert((from_id.clone(), to_id.clone()));
+        for (neighbour, from_ranges, dispatch) in neighbours {
+            let neighbour_id = call_item_key(&neighbour);
+            if !state.node_info.contains_key(&neighbour_id) {
+                let Some(node) = neighbour_node(client, &neighbour, direction, options, meta_ctx)
+                else {
+                    continue;
+                };
+                state.node_info.insert(neighbour_id.clone(), node);
+            }
+            let caller_uri = match direction {
+                TraversalDirection::Callees => &item.uri,
+                TraversalDirection::Callers => &neighbour.uri,
+            };
+            let edge = directed_edge(direction, &item_id, &neighbour_id);
+            if dispatch {
+                state.dispatch_edges.insert(edge.clone());
+            }
+            state
+                .visited_edges
+                .entry(edge)
+                .or_default()
+                .extend(call_sites_from_ranges(
+                    caller_uri,
+                    &from_ranges,
+                    meta_ctx.workspace_root_path,
+                ));
 
-            if !visited_nodes.contains(&to_id) {
-                stack.push(child);
+            if client.is_uri_in_workspace(&neighbour.uri)
+                && state.needs_visit(&neighbour_id, depth + 1)
+            {
+                queue.push_back((neighbour, depth + 1));
             }
         }
     }
@@ -242,19 +492,265 @@ async fn traverse_items(
     Ok(())
 }
 
+/// Builds the node for `neighbour`, or `None` when it is left out of the graph: outside the
+/// workspace and not kept by [`TraversalOptions::external`], or rejected by the filter.
+fn neighbour_node(
+    client: &lsp::LspClient,
+    neighbour: &CallHierarchyItem,
+    direction: TraversalDirection,
+    options: &TraversalOptions,
+    meta_ctx: &MetaContext<'_>,
+) -> Option<CallGraphNode> {
+    let node = if client.is_uri_in_workspace(&neighbour.uri) {
+        node_from_item(neighbour, meta_ctx)
+    } else if direction == TraversalDirection::Callees {
+        external_node_from_item(neighbour, options.external)?
+    } else {
+        return None;
+    };
+    options.filter.allows(&node).then_some(node)
+}
+
+/// Fetches the items adjacent to `item` in `direction`, each with its `fromRanges`.
+///
+/// `fromRanges` always lie in the caller's file: `item` itself for outgoing calls, the
+/// returned neighbour for incoming calls.
+async fn fetch_neighbours(
+    client: &mut lsp::LspClient,
+    item: &CallHierarchyItem,
+    direction: TraversalDirection,
+) -> Result<Vec<(CallHierarchyItem, Vec<Range>)>, CallGraphError> {
+    let neighbours = match direction {
+        TraversalDirection::Callees => client
+            .call_hierarchy_outgoing_calls(item)
+            .await?
+            .into_iter()
+            .map(|call| (call.to, call.from_ranges))
+            .collect(),
+        TraversalDirection::Callers => client
+            .call_hierarchy_incoming_calls(item)
+            .await?
+            .into_iter()
+            .map(|call| (call.from, call.from_ranges))
+            .collect(),
+    };
+    Ok(neighbours)
+}
+
+/// Fetches the functions implementing the trait method `item`, skipping locations that are
+/// not a function (such as the trait declaration itself).
+async fn fetch_implementations(
+    client: &mut lsp::LspClient,
+    item: &CallHierarchyItem,
+) -> Result<Vec<CallHierarchyItem>, CallGraphError> {
+    let item_id = call_item_key(item);
+    let mut implementations = Vec::new();
+    for location in client
+        .text_document_implementation(&item.uri, item.selection_range.start)
+        .await?
+    {
+        let items = client
+            .text_document_prepare_call_hierarchy_at(&location.uri, location.range.start)
+            .await?;
+        implementations.extend(items.into_iter().filter(|i| call_item_key(i) != item_id));
+    }
+    Ok(implementations)
+}
+
+/// Builds the graph node for `item`, resolving its label/group via [`meta_resolver`] and
+/// copying the location, kind, signature and tags the call hierarchy item already carries.
+/// Its id is the [`identity::function_path`], not yet told apart from other nodes'.
+fn node_from_item(item: &CallHierarchyItem, meta_ctx: &MetaContext<'_>) -> CallGraphNode {
+    let meta = meta_resolver::resolve_function_meta(
+        item,
+        meta_ctx.function_symbols,
+        meta_ctx.workspace_root_path,
+        meta_ctx.crate_name,
+    );
+    let file = meta_resolver::workspace_relative_path(&item.uri, meta_ctx.workspace_root_path);
+    let path = identity::function_path(
+        meta_ctx.crate_name,
+        &file,
+        meta_resolver::resolve_impl_block(item).as_ref(),
+        &item.name,
+    );
+    let suffix = identity::duplicate_suffix(
+        &path,
+        item.range.start.line,
+        &sibling_paths(item, &file, meta_ctx),
+    );
+    CallGraphNode {
+        id: path + suffix.as_deref().unwrap_or(""),
+        label: meta.qualified_label,
+        group: meta.group,
+        location: Some(SourceLocation {
+            file,
+            start_line: item.range.start.line + 1,
+            end_line: item.range.end.line + 1,
+        }),
+        kind: function_kind(item),
+        detail: item.detail.clone(),
+        deprecated: is_deprecated(item),
+        is_test: meta_resolver::is_in_test_code(item, meta_ctx.workspace_root_path),
+        truncated: false,
+        is_root: false,
+        external: false,
+        highlight: None,
+        size: None,
+    }
+}
+
+/// Paths and lines of the workspace functions named like `item` in its file, for
+/// [`identity::duplicate_suffix`]. Empty when `item` is the only one, sparing the source
+/// reads that resolving impl blocks takes.
+fn sibling_paths(
+    item: &CallHierarchyItem,
+    file: &str,
+    meta_ctx: &MetaContext<'_>,
+) -> Vec<(String, u32)> {
+    let siblings: Vec<&SymbolInformation> = meta_ctx
+        .function_symbols
+        .iter()
+        .filter(|s| {
+            (s.kind == SymbolKind::FUNCTION || s.kind == SymbolKind::METHOD)
+                && s.name == item.name
+                && s.location.uri == item.uri
+        })
+        .collect();
+    if siblings.len() < 2 {
+        return Vec::new();
+    }
+    siblings
+        .into_iter()
+        .map(|symbol| {
+            let at_symbol = CallHierarchyItem {
+                name: symbol.name.clone(),
+                kind: symbol.kind,
+                tags: None,
+                detail: None,
+                uri: symbol.location.uri.clone(),
+                range: symbol.location.range,
+                selection_range: symbol.location.range,
+                data: None,
+            };
+            let path = identity::function_path(
+                meta_ctx.crate_name,
+                file,
+                meta_resolver::resolve_impl_block(&at_symbol).as_ref(),
+                &symbol.name,
+            );
+            (path, symbol.location.range.start.line)
+        })
+        .collect()
+}
+
+/// Builds the leaf node for `item`, a function outside the workspace, when `external` keeps
+/// its crate. The node's group is the crate, its id the function's path within that crate
+/// and its location the absolute path of the defining file.
+fn external_node_from_item(
+    item: &CallHierarchyItem,
+    external: ExternalCalls,
+) -> Option<CallGraphNode> {
+    let path = item.uri.to_file_path().ok()?;
+    let origin = external::crate_of(&path)?;
+    if !external.keeps(origin.kind) {
+        return None;
+    }
+    let impl_block = meta_resolver::resolve_impl_block(item);
+    let label = match &impl_block {
+        Some(block) => format!("{}::{}", block.self_type, item.name),
+        None => item.name.clone(),
+    };
+    Some(CallGraphNode {
+        id: identity::function_path(&origin.name, &origin.file, impl_block.as_ref(), &item.name),
+        label,
+        group: origin.name,
+        location: Some(SourceLocation {
+            file: path.to_string_lossy().into_owned(),
+            start_line: item.range.start.line + 1,
+            end_line: item.range.end.line + 1,
+        }),
+        kind: function_kind(item),
+        detail: item.detail.clone(),
+        deprecated: is_deprecated(item),
+        external: true,
+        ..Default::default()
+    })
+}
+
+fn function_kind(item: &CallHierarchyItem) -> FunctionKind {
+    if item.kind == SymbolKind::METHOD {
+        FunctionKind::Method
+    } else {
+        FunctionKind::Function
+    }
+}
+
+fn is_deprecated(item: &CallHierarchyItem) -> bool {
+    item.tags
+        .as_ref()
+        .is_some_and(|tags| tags.contains(&SymbolTag::DEPRECATED))
+}
+
+/// Orients the edge between a visited item and one of its neighbours so that it always
+/// points from caller to callee, regardless of the traversal direction.
+fn directed_edge(
+    direction: TraversalDirection,
+    item_id: &str,
+    neighbour_id: &str,
+) -> (String, String) {
+    match direction {
+        TraversalDirection::Callees => (item_id.to_string(), neighbour_id.to_string()),
+        TraversalDirection::Callers => (neighbour_id.to_string(), item_id.to_string()),
+    }
+}
+
+/// Converts LSP `fromRanges` (0-based) in `caller_uri` into 1-based [`CallSite`]s with
+/// workspace-relative file paths.
+fn call_sites_from_ranges(
+    caller_uri: &Url,
+    from_ranges: &[Range],
+    workspace_root_path: &std::path::Path,
+) -> Vec<CallSite> {
+    let file = meta_resolver::workspace_relative_path(caller_uri, workspace_root_path);
+    from_ranges
+        .iter()
+        .map(|range| CallSite {
+            file: file.clone(),
+            line: range.start.line + 1,
+            column: range.start.character + 1,
+        })
+        .collect()
+}
+
+/// Assembles the graph from the nodes and edges collected under traversal keys, numbering
+/// duplicate node ids (see [`identity::number_duplicates`]) and pointing the edges at the
+/// final ids. Edges in `dispatch_edges` are marked as dispatch edges.
 fn build_call_graph(
-    node_info: HashMap<String, (String, String)>,
-    visited_edges: HashSet<(String, String)>,
+    node_info: HashMap<String, CallGraphNode>,
+    visited_edges: EdgeSites,
+    dispatch_edges: &HashSet<(String, String)>,
 ) -> CallGraph {
-    let mut nodes: Vec<CallGraphNode> = node_info
+    let (keys, mut nodes): (Vec<String>, Vec<CallGraphNode>) = node_info.into_iter().unzip();
+    identity::number_duplicates(&mut nodes);
+    let id_of: HashMap<String, String> = keys
         .into_iter()
-        .map(|(id, (label, group))| CallGraphNode { id, label, group })
+        .zip(nodes.iter().map(|node| node.id.clone()))
         .collect();
     nodes.sort_by(|a, b| a.id.cmp(&b.id));
 
     let mut edges: Vec<CallGraphEdge> = visited_edges
         .into_iter()
-        .map(|(from, to)| CallGraphEdge { from, to })
+        .filter_map(|((from, to), call_sites)| {
+            let dispatch = dispatch_edges.contains(&(from.clone(), to.clone()));
+            Some(CallGraphEdge {
+                from: id_of.get(&from)?.clone(),
+                to: id_of.get(&to)?.clone(),
+                call_sites: call_sites.into_iter().collect(),
+                highlight: None,
+                dispatch,
+            })
+        })
         .collect();
     edges.sort_by(|a, b| a.from.cmp(&b.from).then(a.to.cmp(&b.to)));
 
@@ -290,6 +786,22 @@ mod tests {
         }
     }
 
+    fn make_node(id: &str, label: &str, group: &str) -> CallGraphNode {
+        CallGraphNode {
+            id: id.to_string(),
+            label: label.to_string(),
+            group: group.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Nodes stored under their own id as traversal key.
+    fn keyed_nodes(ids: &[&str]) -> HashMap<String, CallGraphNode> {
+        ids.iter()
+            .map(|id| (id.to_string(), make_node(id, id, "g")))
+            .collect()
+    }
+
     // --- call_item_key ---
 
     #[test]
@@ -326,34 +838,117 @@ mod tests {
         assert_ne!(call_item_key(&a), call_item_key(&b));
     }
 
+    // --- node_from_item ---
+
+    #[test]
+    fn node_from_item_copies_location_kind_detail_and_tags() {
+        let mut item = make_call_hierarchy_item("run", "file:///workspace/src/app.rs", 12, 7);
+        item.range.end.line = 40;
+        item.kind = SymbolKind::METHOD;
+        item.detail = Some("pub async fn run(config: Config)".to_string());
+        item.tags = Some(vec![SymbolTag::DEPRECATED]);
+        let meta_ctx = MetaContext {
+            function_symbols: &[],
+            workspace_root_path: std::path::Path::new("/workspace"),
+            crate_name: "my_crate",
+        };
+
+        let node = node_from_item(&item, &meta_ctx);
+
+        assert_eq!(node.id, "my_crate::app::run");
+        assert_eq!(
+            node.location,
+            Some(SourceLocation {
+                file: "src/app.rs".to_string(),
+                start_line: 13,
+                end_line: 41,
+            })
+        );
+        assert_eq!(node.kind, FunctionKind::Method);
+        assert_eq!(
+            node.detail.as_deref(),
+            Some("pub async fn run(config: Config)")
+        );
+        assert!(node.deprecated);
+        assert!(!node.is_test);
+    }
+
+    #[test]
+    fn external_node_from_item_groups_by_crate_when_kept() {
+        let mut item = make_call_hierarchy_item(
+            "from_str",
+            "file:///home/dev/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde_json-1.0.108/src/de.rs",
+            2640,
+            7,
+        );
+        item.detail = Some("pub fn from_str<'a, T>(s: &'a str) -> Result<T>".to_string());
+        let deps = ExternalCalls {
+            dependencies: true,
+            ..Default::default()
+        };
+
+        let node = external_node_from_item(&item, deps).unwrap();
+
+        assert_eq!(node.id, "serde_json::de::from_str");
+        assert_eq!(node.label, "from_str");
+        assert_eq!(node.group, "serde_json");
+        assert!(node.external);
+        assert!(node
+            .location
+            .unwrap()
+            .file
+            .ends_with("serde_json-1.0.108/src/de.rs"));
+
+        let std_only = ExternalCalls {
+            std: true,
+            ..Default::default()
+        };
+        assert!(external_node_from_item(&item, std_only).is_none());
+    }
+
+    // --- directed_edge ---
+
+    #[test]
+    fn directed_edge_callees_points_from_item_to_neighbour() {
+        let edge = directed_edge(TraversalDirection::Callees, "item", "callee");
+        assert_eq!(edge, ("item".to_string(), "callee".to_string()));
+    }
+
+    #[test]
+    fn directed_edge_callers_points_from_neighbour_to_item() {
+        let edge = directed_edge(TraversalDirection::Callers, "item", "caller");
+        assert_eq!(edge, ("caller".to_string(), "item".to_string()));
+    }
+
     // --- build_call_graph ---
 
     #[test]
     fn build_call_graph_empty_input_produces_empty_graph() {
-        let graph = build_call_graph(HashMap::new(), HashSet::new());
+        let graph = build_call_graph(HashMap::new(), HashMap::new(), &HashSet::new());
         assert!(graph.nodes.is_empty());
         assert!(graph.edges.is_empty());
     }
 
     #[test]
     fn build_call_graph_nodes_are_sorted_by_id() {
-        let mut node_info = HashMap::new();
-        node_info.insert("c::foo".to_string(), ("foo".to_string(), "c".to_string()));
-        node_info.insert("a::bar".to_string(), ("bar".to_string(), "a".to_string()));
-        node_info.insert("b::baz".to_string(), ("baz".to_string(), "b".to_string()));
-        let graph = build_call_graph(node_info, HashSet::new());
+        let node_info = HashMap::from([
+            ("c::foo".to_string(), make_node("c::foo", "foo", "c")),
+            ("a::bar".to_string(), make_node("a::bar", "bar", "a")),
+            ("b::baz".to_string(), make_node("b::baz", "baz", "b")),
+        ]);
+        let graph = build_call_graph(node_info, HashMap::new(), &HashSet::new());
         let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
         assert_eq!(ids, vec!["a::bar", "b::baz", "c::foo"]);
     }
 
     #[test]
     fn build_call_graph_edges_are_sorted_by_from_then_to() {
-        let edges = HashSet::from([
-            ("a".to_string(), "z".to_string()),
-            ("b".to_string(), "c".to_string()),
-            ("a".to_string(), "b".to_string()),
+        let edges: EdgeSites = HashMap::from([
+            (("a".to_string(), "z".to_string()), BTreeSet::new()),
+            (("b".to_string(), "c".to_string()), BTreeSet::new()),
+            (("a".to_string(), "b".to_string()), BTreeSet::new()),
         ]);
-        let graph = build_call_graph(HashMap::new(), edges);
+        let graph = build_call_graph(keyed_nodes(&["a", "b", "c", "z"]), edges, &HashSet::new());
         let pairs: Vec<(&str, &str)> = graph
             .edges
             .iter()
@@ -364,15 +959,190 @@ mod tests {
 
     #[test]
     fn build_call_graph_node_label_and_group_are_preserved() {
-        let mut node_info = HashMap::new();
-        node_info.insert(
-            "id1".to_string(),
-            ("my::label".to_string(), "my_group".to_string()),
-        );
-        let graph = build_call_graph(node_info, HashSet::new());
+        let node_info =
+            HashMap::from([("id1".to_string(), make_node("id1", "my::label", "my_group"))]);
+        let graph = build_call_graph(node_info, HashMap::new(), &HashSet::new());
         assert_eq!(graph.nodes.len(), 1);
         assert_eq!(graph.nodes[0].id, "id1");
         assert_eq!(graph.nodes[0].label, "my::label");
         assert_eq!(graph.nodes[0].group, "my_group");
     }
+
+    #[test]
+    fn build_call_graph_edge_call_sites_are_sorted_and_counted() {
+        let site = |line| CallSite {
+            file: "src/app.rs".to_string(),
+            line,
+            column: 5,
+        };
+        let edges: EdgeSites = HashMap::from([(
+            ("a".to_string(), "b".to_string()),
+            BTreeSet::from([site(30), site(12), site(12)]),
+        )]);
+        let graph = build_call_graph(keyed_nodes(&["a", "b"]), edges, &HashSet::new());
+        assert_eq!(graph.edges[0].call_count(), 2);
+        assert_eq!(graph.edges[0].call_sites, vec![site(12), site(30)]);
+    }
+
+    #[test]
+    fn build_call_graph_numbers_duplicate_ids_and_repoints_edges() {
+        let mut first = make_node("p::new", "new", "p");
+        first.location = Some(SourceLocation {
+            file: "src/p.rs".to_string(),
+            start_line: 3,
+            end_line: 5,
+        });
+        let mut second = first.clone();
+        second.location = Some(SourceLocation {
+            file: "src/p.rs".to_string(),
+            start_line: 30,
+            end_line: 32,
+        });
+        let node_info = HashMap::from([
+            ("key30".to_string(), second),
+            ("key3".to_string(), first),
+            ("main".to_string(), make_node("main", "main", "crate")),
+        ]);
+        let edges: EdgeSites =
+            HashMap::from([(("main".to_string(), "key30".to_string()), BTreeSet::new())]);
+        let graph = build_call_graph(node_info, edges, &HashSet::new());
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["main", "p::new#1", "p::new#2"]);
+        assert_eq!(graph.edges[0].to, "p::new#2");
+    }
+
+    // --- TraversalState::visit ---
+
+    #[test]
+    fn item_truncated_at_depth_limit_is_expanded_again_as_root() {
+        let mut state = TraversalState::default();
+        state.node_info.insert(
+            "helper".to_string(),
+            CallGraphNode {
+                id: "helper".to_string(),
+                ..Default::default()
+            },
+        );
+
+        // First reached two calls away from an earlier root, at the depth limit.
+        assert!(state.visit("helper", 2));
+        state.node_info.get_mut("helper").unwrap().truncated = true;
+        assert!(!state.visit("helper", 2));
+        assert!(!state.needs_visit("helper", 3));
+
+        // Later reached as a root of the all-symbols traversal.
+        assert!(state.needs_visit("helper", 0));
+        assert!(state.visit("helper", 0));
+        assert!(!state.node_info["helper"].truncated);
+        assert!(!state.visit("helper", 1));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn duplicate_ids_do_not_change_when_another_becomes_reachable() {
+        let uri = "file:///workspace/src/point.rs";
+        let symbol = |line: u32| SymbolInformation {
+            name: "new".to_string(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            deprecated: None,
+            location: lsp_types::Location {
+                uri: Url::parse(uri).unwrap(),
+                range: Range {
+                    start: Position { line, character: 7 },
+                    end: Position {
+                        line,
+                        character: 10,
+                    },
+                },
+            },
+            container_name: None,
+        };
+        let symbols = [symbol(10), symbol(40)];
+        let meta_ctx = MetaContext {
+            function_symbols: &symbols,
+            workspace_root_path: std::path::Path::new("/workspace"),
+            crate_name: "my_crate",
+        };
+        let first = make_call_hierarchy_item("new", uri, 9, 0);
+        let second = make_call_hierarchy_item("new", uri, 39, 0);
+        let graph_of = |items: &[&CallHierarchyItem]| {
+            let node_info = items
+                .iter()
+                .map(|item| (call_item_key(item), node_from_item(item, &meta_ctx)))
+                .collect();
+            let ids: Vec<String> = build_call_graph(node_info, EdgeSites::new(), &HashSet::new())
+                .nodes
+                .into_iter()
+                .map(|node| node.id)
+                .collect();
+            ids
+        };
+
+        assert_eq!(graph_of(&[&second]), vec!["my_crate::point::new#2"]);
+        assert_eq!(
+            graph_of(&[&first, &second]),
+            vec!["my_crate::point::new#1", "my_crate::point::new#2"]
+        );
+    }
+
+    #[test]
+    fn excluded_workspace_functions_are_not_traversed() {
+        let meta_ctx = MetaContext {
+            function_symbols: &[],
+            workspace_root_path: std::path::Path::new("/workspace"),
+            crate_name: "my_crate",
+        };
+        let filter = NodeFilter {
+            exclude: vec![crate::call_graph::filter::Rule::file("src/lsp/**").unwrap()],
+            ..Default::default()
+        };
+        let items = vec![
+            make_call_hierarchy_item("run", "file:///workspace/src/app.rs", 3, 0),
+            make_call_hierarchy_item("send", "file:///workspace/src/lsp/client.rs", 9, 0),
+        ];
+        let names: Vec<String> = allowed_items(items, &filter, &meta_ctx)
+            .into_iter()
+            .map(|item| item.name)
+            .collect();
+        assert_eq!(names, vec!["run"]);
+    }
+
+    #[test]
+    fn build_call_graph_marks_dispatch_edges() {
+        let edges: EdgeSites = HashMap::from([
+            (("a".to_string(), "b".to_string()), BTreeSet::new()),
+            (("b".to_string(), "c".to_string()), BTreeSet::new()),
+        ]);
+        let dispatch = HashSet::from([("b".to_string(), "c".to_string())]);
+        let graph = build_call_graph(keyed_nodes(&["a", "b", "c"]), edges, &dispatch);
+        let flags: Vec<bool> = graph.edges.iter().map(|e| e.dispatch).collect();
+        assert_eq!(flags, vec![false, true]);
+    }
+
+    // --- call_sites_from_ranges ---
+
+    #[test]
+    fn call_sites_from_ranges_are_one_based_and_workspace_relative() {
+        let uri = Url::parse("file:///workspace/src/app.rs").unwrap();
+        let range = Range {
+            start: Position {
+                line: 9,
+                character: 4,
+            },
+            end: Position {
+                line: 9,
+                character: 12,
+            },
+        };
+        let sites = call_sites_from_ranges(&uri, &[range], std::path::Path::new("/workspace"));
+        assert_eq!(
+            sites,
+            vec![CallSite {
+                file: "src/app.rs".to_string(),
+                line: 10,
+                column: 5,
+            }]
+        );
+    }
 }
@@ -1,7 +1,8 @@
 //! CLI argument parsing. Parses raw arguments via `clap` and validates them before
 //! producing a [`Config`] for the rest of the application.
 
-use clap::Parser;
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, Parser, ValueEnum};
 use std::path::{Path, PathBuf};
 
 /// Runtime configuration produced from validated CLI arguments.
@@ -11,11 +12,225 @@ use std::path::{Path, PathBuf};
 pub struct Config {
     /// Absolute path to the Rust workspace root (must contain `Cargo.toml`).
     pub workspace: String,
-    /// Optional entry function name to start call graph traversal from.
-    /// When `None`, the tool traverses all workspace functions.
-    pub entry_function: Option<String>,
-    /// Path where the generated DOT file will be written.
-    pub output_path: String,
+    /// Entry function names to start call graph traversal from, merged into one graph.
+    /// When empty, the tool traverses all workspace functions.
+    pub entry_functions: Vec<String>,
+    /// Path where the rendered graph will be written, or `-` for stdout. `None` when only a
+    /// report was requested and no graph should be written.
+    pub output_path: Option<String>,
+    /// Explicit output format name. When `None`, the format is inferred from `output_path`.
+    pub format: Option<String>,
+    /// Whether to follow callees from the entry function or callers of it.
+    pub direction: Direction,
+    /// Previously saved JSON graph to load instead of running rust-analyzer.
+    pub input_path: Option<String>,
+    /// Maximum call distance from the entry function(s) to expand. `None` means unlimited.
+    pub max_depth: Option<usize>,
+    /// Follow calls to trait methods on to every implementation.
+    pub expand_dispatch: bool,
+    /// Kinds of callees outside the workspace kept as leaf nodes.
+    pub external: Vec<ExternalCallees>,
+    /// Include/exclude patterns, still uncompiled.
+    pub filter: FilterSpec,
+    /// When the include/exclude patterns are applied.
+    pub filter_mode: FilterMode,
+    /// Target function of a path query. The entry function is the source.
+    pub path_to: Option<String>,
+    /// Which paths a path query reports.
+    pub path_mode: PathMode,
+    /// Maximum number of paths listed by [`PathMode::All`].
+    pub max_paths: usize,
+    /// Analysis report to print.
+    pub report: Option<Report>,
+    /// How the report is printed.
+    pub report_format: ReportFormat,
+    /// Entry points of the dead-code report, besides the entry function.
+    pub roots: Vec<RootKind>,
+    /// Architecture rules file checked by the layering report.
+    pub rules_path: Option<String>,
+    /// Saved JSON graph to compare the current graph against.
+    pub diff_against: Option<String>,
+    /// Collapse functions into a module-level graph before reporting and rendering.
+    pub collapse: Option<CollapseBy>,
+    /// Number of module path segments kept by [`CollapseBy::Module`]. `None` keeps all.
+    pub module_depth: Option<usize>,
+    /// Saved JSON graph holding the calls the baseline report accepts.
+    pub baseline_path: Option<String>,
+    /// Which calls the baseline report checks.
+    pub baseline_scope: BaselineScope,
+    /// Overwrite the baseline with the current graph before checking against it.
+    pub update_baseline: bool,
+    /// Change whose impact is analysed instead of traversing from entry functions.
+    pub impact: Option<ImpactSource>,
+    /// Metric the stats report is sorted by.
+    pub sort_by: Metric,
+    /// Number of functions listed by the stats report. 0 lists all.
+    pub top: usize,
+    /// Metric that sets the node sizes of the rendered graph.
+    pub scale_nodes: Option<Metric>,
+}
+
+/// Where the change of a change-impact analysis comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImpactSource {
+    /// A unified diff file.
+    Patch(String),
+    /// `OLD..NEW`, or a single revision compared with the working tree.
+    Revisions(String),
+}
+
+/// Output path used when neither `OUTPUT_PATH` nor `--report` is given.
+pub const DEFAULT_OUTPUT_PATH: &str = "tmp/callgraph.dot";
+
+/// Number of functions `--report stats` lists when `--top` is not given.
+const DEFAULT_TOP: usize = 20;
+
+/// Textual analysis reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Report {
+    /// Direct and mutual recursion cycles, with call-site lines.
+    Cycles,
+    /// Workspace functions that no entry point (see `--roots`) reaches, grouped by module.
+    DeadCode,
+    /// Calls that break the architecture rules given with `--rules`.
+    Layering,
+    /// Calls that the graph given with `--baseline` does not have.
+    Baseline,
+    /// The `#[test]` functions that reach each function, and the functions no test
+    /// reaches. Entry functions, when given, narrow the index to those functions.
+    Tests,
+    /// Fan-in, fan-out, transitive reach, depth and betweenness centrality of the `--top`
+    /// functions by `--sort-by`.
+    Stats,
+}
+
+/// Output format of `--report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    /// SARIF 2.1.0, for code review tools that show findings inline.
+    Sarif,
+    /// Comma-separated values, for `--report stats`.
+    Csv,
+}
+
+/// Per-function metrics of `--report stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Metric {
+    /// Number of distinct callers.
+    FanIn,
+    /// Number of distinct callees.
+    FanOut,
+    /// Number of functions reachable through calls.
+    Reach,
+    /// Fewest calls from an entry function (or from a function nobody calls).
+    Depth,
+    /// Number of shortest call paths between other functions passing through it.
+    Betweenness,
+}
+
+/// Kinds of functions used as entry points by the dead-code report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RootKind {
+    /// Every function named `main`.
+    Main,
+    /// Every `pub` function (the public API of a library).
+    Pub,
+    /// Every function in test code.
+    Tests,
+}
+
+/// Kinds of functions outside the workspace that `--external` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExternalCallees {
+    /// The standard library crates (`std`, `core`, `alloc`, ...).
+    Std,
+    /// Every other crate: registry, git and path dependencies.
+    Deps,
+}
+
+/// What functions are collapsed into for a module-level graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CollapseBy {
+    /// Their group (the cluster name in the output).
+    Group,
+    /// Their module path, derived from the defining file.
+    Module,
+}
+
+/// Which calls `--report baseline` checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BaselineScope {
+    /// Every call.
+    All,
+    /// Only calls from one group (cluster) into another.
+    CrossGroup,
+}
+
+/// Include/exclude patterns selecting which functions appear in the graph.
+///
+/// Every option may be repeated. A function is kept when it matches no exclude pattern and,
+/// if any include pattern is given, at least one of those.
+#[derive(Debug, Clone, Default, Args)]
+pub struct FilterSpec {
+    /// Keep functions defined in files matching this glob (relative to the workspace root).
+    #[arg(long = "include-file", value_name = "GLOB")]
+    pub include_files: Vec<String>,
+    /// Drop functions defined in files matching this glob (relative to the workspace root).
+    #[arg(long = "exclude-file", value_name = "GLOB")]
+    pub exclude_files: Vec<String>,
+    /// Keep functions whose qualified name (e.g. `MyStruct::method`) matches this regex.
+    #[arg(long = "include-name", value_name = "REGEX")]
+    pub include_names: Vec<String>,
+    /// Drop functions whose qualified name (e.g. `MyStruct::method`) matches this regex.
+    #[arg(long = "exclude-name", value_name = "REGEX")]
+    pub exclude_names: Vec<String>,
+    /// Keep functions in this group (the cluster name in the output).
+    #[arg(long = "include-group", value_name = "GROUP")]
+    pub include_groups: Vec<String>,
+    /// Drop functions in this group (the cluster name in the output).
+    #[arg(long = "exclude-group", value_name = "GROUP")]
+    pub exclude_groups: Vec<String>,
+}
+
+impl FilterSpec {
+    pub fn is_empty(&self) -> bool {
+        self.include_files.is_empty()
+            && self.exclude_files.is_empty()
+            && self.include_names.is_empty()
+            && self.exclude_names.is_empty()
+            && self.include_groups.is_empty()
+            && self.exclude_groups.is_empty()
+    }
+}
+
+/// When include/exclude patterns are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FilterMode {
+    /// Skip filtered functions during traversal; nothing behind them is explored.
+    Traverse,
+    /// Build the full graph, then remove filtered functions and connect their callers
+    /// directly to their callees.
+    Reconnect,
+}
+
+/// Direction in which the call graph is explored from the entry function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Direction {
+    /// Functions called by the entry function, transitively.
+    Callees,
+    /// Functions that call the entry function, transitively.
+    Callers,
+}
+
+/// Which paths between the entry function and `--path-to` are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PathMode {
+    /// One path with the fewest calls.
+    Shortest,
+    /// Every path that visits no function twice, up to `--max-paths`.
+    All,
 }
 
 /// Returns `Ok(())` when `path` is a valid Rust project root,
@@ -54,9 +269,105 @@ pub fn validate_rust_workspace(path: &Path) -> anyhow::Result<()> {
 #[command(about = "Generate call graph dot output via rust-analyzer", long_about = None)]
 pub struct Cli {
     workspace: Option<String>,
+    /// Function to start from: a name, a qualified path (`Type::method`) or `file.rs:line`.
+    /// More can be added with --entry; an empty string means none.
     pub entry_function: Option<String>,
-    #[arg(default_value = "tmp/callgraph.dot")]
-    pub output_path: String,
+    /// Output file, or `-` to write to stdout [default: tmp/callgraph.dot, or no graph when
+    /// --report is given]
+    pub output_path: Option<String>,
+    /// Output format (dot, json, mermaid). Inferred from the output file extension when omitted.
+    #[arg(long)]
+    pub format: Option<String>,
+    /// Traverse callees of the entry function (default) or callers of it.
+    #[arg(long, value_enum, default_value_t = Direction::Callees)]
+    pub direction: Direction,
+    /// Additional entry function; may be repeated. All entries end up in one merged graph.
+    #[arg(long = "entry", value_name = "NAME")]
+    pub entries: Vec<String>,
+    /// Load a graph saved as JSON instead of analysing the workspace with rust-analyzer.
+    #[arg(long, value_name = "GRAPH_JSON")]
+    pub input: Option<String>,
+    /// Stop expanding functions more than N calls away from the entry function(s).
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+    /// Continue from a called trait method to every implementation of it, drawn with a
+    /// dashed "may dispatch to" edge. Applies when following callees.
+    #[arg(long)]
+    pub expand_dispatch: bool,
+    /// Keep calls out of the workspace as leaf nodes grouped by crate, comma-separated:
+    /// `std` for std/core/alloc, `deps` for dependencies. Applies when following callees.
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "KINDS")]
+    pub external: Vec<ExternalCallees>,
+    #[command(flatten)]
+    pub filter: FilterSpec,
+    /// Apply include/exclude patterns while traversing (default) or afterwards, reconnecting
+    /// callers of removed functions to their callees.
+    #[arg(long, value_enum, default_value_t = FilterMode::Traverse)]
+    pub filter_mode: FilterMode,
+    /// Keep only the call paths from the entry function to this function, and print them.
+    #[arg(long, value_name = "TARGET")]
+    pub path_to: Option<String>,
+    /// Report the shortest path (default) or all simple paths to `--path-to`.
+    #[arg(long, value_enum, default_value_t = PathMode::Shortest)]
+    pub path_mode: PathMode,
+    /// Maximum number of paths reported with `--path-mode all`.
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub max_paths: usize,
+    /// Print an analysis report. The graph is then only written when OUTPUT_PATH is given.
+    #[arg(long, value_enum)]
+    pub report: Option<Report>,
+    /// Format of the `--report` output.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub report_format: ReportFormat,
+    /// Entry points for `--report dead-code`, comma-separated. The entry function, when given,
+    /// is always one of them.
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [RootKind::Main, RootKind::Tests])]
+    pub roots: Vec<RootKind>,
+    /// Architecture rules file (TOML) for `--report layering`. The command fails when any
+    /// call breaks a rule.
+    #[arg(long = "rules", value_name = "RULES_TOML")]
+    pub rules: Option<String>,
+    /// Compare the graph with this saved JSON graph: print the added and removed functions
+    /// and calls, and render both combined with additions green and removals red.
+    #[arg(long, value_name = "BASELINE_JSON")]
+    pub diff_against: Option<String>,
+    /// Render a module-level graph: one node per group or module, edges counting the calls
+    /// between them.
+    #[arg(long, value_enum)]
+    pub collapse: Option<CollapseBy>,
+    /// With `--collapse module`, keep only the first N module path segments (1 turns
+    /// `lsp::client` into `lsp`).
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub module_depth: Option<usize>,
+    /// Saved JSON graph of the accepted calls for `--report baseline`. The command fails when
+    /// the graph has calls the baseline does not.
+    #[arg(long, value_name = "BASELINE_JSON")]
+    pub baseline: Option<String>,
+    /// Check every call (default) or only calls between groups against the baseline.
+    #[arg(long, value_enum, default_value_t = BaselineScope::All)]
+    pub baseline_scope: BaselineScope,
+    /// Accept the current calls: write the graph to the `--baseline` file before checking.
+    #[arg(long)]
+    pub update_baseline: bool,
+    /// Show what the change in this unified diff affects: the changed functions and every
+    /// function that transitively calls them. File paths in the diff must be relative to the
+    /// workspace (e.g. `git diff --relative` run in it).
+    #[arg(long, value_name = "PATCH_FILE", conflicts_with = "impact_revs")]
+    pub impact_patch: Option<String>,
+    /// Like --impact-patch, for the git changes `OLD..NEW` or `OLD...NEW` (or from one revision
+    /// to the working tree). Lines are matched against the files on disk, so NEW must be the
+    /// checked-out HEAD with no uncommitted changes.
+    #[arg(long, value_name = "REVISIONS")]
+    pub impact_revs: Option<String>,
+    /// Metric `--report stats` sorts by, highest first [default: betweenness].
+    #[arg(long, value_enum, value_name = "METRIC")]
+    pub sort_by: Option<Metric>,
+    /// Number of functions `--report stats` lists; 0 lists all [default: 20].
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+    /// Draw nodes larger the higher this metric is (DOT output).
+    #[arg(long, value_enum, value_name = "METRIC")]
+    pub scale_nodes: Option<Metric>,
 }
 
 impl Cli {
@@ -78,12 +389,214 @@ impl Cli {
 
         let workspace_path = std::fs::canonicalize(&raw).unwrap_or_else(|_| PathBuf::from(&raw));
 
-        validate_rust_workspace(&workspace_path)?;
+        // A saved graph needs no workspace: rust-analyzer is never started.
+        if self.input.is_none() {
+            validate_rust_workspace(&workspace_path)?;
+        }
+
+        let entry_functions: Vec<String> = self
+            .entry_function
+            .into_iter()
+            .chain(self.entries)
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        if self.direction == Direction::Callers && entry_functions.is_empty() {
+            return Err(anyhow::anyhow!(
+                "--direction callers requires an entry function to use as the target"
+            ));
+        }
+
+        if self.path_to.is_some() {
+            if entry_functions.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "--path-to requires an entry function to use as the path source"
+                ));
+            }
+            if self.direction == Direction::Callers {
+                return Err(anyhow::anyhow!(
+                    "--path-to follows calls from the entry function and cannot be combined with --direction callers"
+                ));
+            }
+        }
+
+        if self.expand_dispatch && (self.input.is_some() || self.direction == Direction::Callers) {
+            return Err(anyhow::anyhow!(
+                "--expand-dispatch asks rust-analyzer for implementations while following callees and cannot be combined with --input or --direction callers"
+            ));
+        }
+        if !self.external.is_empty()
+            && (self.input.is_some() || self.direction == Direction::Callers)
+        {
+            return Err(anyhow::anyhow!(
+                "--external keeps callees outside the workspace while traversing and cannot be combined with --input or --direction callers"
+            ));
+        }
+
+        if self.input.is_some()
+            && !self.filter.is_empty()
+            && self.filter_mode == FilterMode::Traverse
+        {
+            return Err(anyhow::anyhow!(
+                "--filter-mode traverse skips functions while traversing and cannot be combined with --input; use --filter-mode reconnect"
+            ));
+        }
+
+        if self.max_depth.is_some() && self.input.is_some() {
+            return Err(anyhow::anyhow!(
+                "--max-depth limits the traversal and cannot be combined with --input"
+            ));
+        }
+        if self.direction == Direction::Callers && self.input.is_some() {
+            return Err(anyhow::anyhow!(
+                "--direction callers traverses callers with rust-analyzer and cannot be combined with --input"
+            ));
+        }
+
+        if self.max_depth.is_some() && matches!(self.report, Some(Report::DeadCode | Report::Tests))
+        {
+            return Err(anyhow::anyhow!(
+                "--report dead-code and tests need every call and cannot be combined with --max-depth"
+            ));
+        }
+
+        if self.module_depth.is_some() && self.collapse != Some(CollapseBy::Module) {
+            return Err(anyhow::anyhow!("--module-depth requires --collapse module"));
+        }
+        if self.collapse.is_some()
+            && matches!(
+                self.report,
+                Some(Report::DeadCode | Report::Layering | Report::Tests)
+            )
+        {
+            return Err(anyhow::anyhow!(
+                "--report dead-code, layering and tests work on functions and cannot be combined with --collapse"
+            ));
+        }
+        if self.report == Some(Report::Tests) {
+            if self.path_to.is_some() || self.direction == Direction::Callers {
+                return Err(anyhow::anyhow!(
+                    "--report tests follows calls from every test and cannot be combined with --path-to or --direction callers"
+                ));
+            }
+            if self.report_format == ReportFormat::Sarif {
+                return Err(anyhow::anyhow!(
+                    "--report tests is printed as text or json, not sarif"
+                ));
+            }
+        }
+        if (self.report == Some(Report::Layering)) != self.rules.is_some() {
+            return Err(anyhow::anyhow!(
+                "--report layering and --rules must be given together"
+            ));
+        }
+
+        if self.report == Some(Report::Stats) {
+            if self.report_format == ReportFormat::Sarif {
+                return Err(anyhow::anyhow!(
+                    "--report stats is printed as text, json or csv, not sarif"
+                ));
+            }
+        } else if self.report_format == ReportFormat::Csv {
+            return Err(anyhow::anyhow!(
+                "--report-format csv is only available for --report stats"
+            ));
+        } else if self.sort_by.is_some() || self.top.is_some() {
+            return Err(anyhow::anyhow!(
+                "--sort-by and --top require --report stats"
+            ));
+        }
+        if self.report.is_none() && self.report_format == ReportFormat::Sarif {
+            return Err(anyhow::anyhow!("--report-format sarif requires --report"));
+        }
+
+        if (self.report == Some(Report::Baseline)) != self.baseline.is_some() {
+            return Err(anyhow::anyhow!(
+                "--report baseline and --baseline must be given together"
+            ));
+        }
+        if self.baseline.is_none()
+            && (self.update_baseline || self.baseline_scope != BaselineScope::All)
+        {
+            return Err(anyhow::anyhow!(
+                "--update-baseline and --baseline-scope require --baseline"
+            ));
+        }
+
+        let impact = match (self.impact_patch, self.impact_revs) {
+            (Some(patch), _) => Some(ImpactSource::Patch(patch)),
+            (None, Some(revisions)) => Some(ImpactSource::Revisions(revisions)),
+            (None, None) => None,
+        };
+        if impact.is_some() {
+            if self.input.is_some() {
+                return Err(anyhow::anyhow!(
+                    "change impact is found with rust-analyzer and cannot be combined with --input"
+                ));
+            }
+            if !entry_functions.is_empty() || self.path_to.is_some() {
+                return Err(anyhow::anyhow!(
+                    "change impact starts from the changed functions and cannot be combined with entry functions or --path-to"
+                ));
+            }
+            if self.report.is_some() || self.diff_against.is_some() {
+                return Err(anyhow::anyhow!(
+                    "change impact prints its own report and cannot be combined with --report or --diff-against"
+                ));
+            }
+            if self.report_format == ReportFormat::Sarif {
+                return Err(anyhow::anyhow!(
+                    "change impact is reported as text or json, not sarif"
+                ));
+            }
+        }
+
+        if self.diff_against.is_some() {
+            if self.report.is_some() {
+                return Err(anyhow::anyhow!(
+                    "--diff-against prints its own report and cannot be combined with --report"
+                ));
+            }
+            if self.report_format == ReportFormat::Sarif {
+                return Err(anyhow::anyhow!(
+                    "--diff-against reports changes as text or json, not sarif"
+                ));
+            }
+        }
 
         Ok(Config {
             workspace: workspace_path.to_string_lossy().to_string(),
-            entry_function: self.entry_function,
-            output_path: self.output_path,
+            entry_functions,
+            output_path: self.output_path.or_else(|| {
+                self.report
+                    .is_none()
+                    .then(|| DEFAULT_OUTPUT_PATH.to_string())
+            }),
+            format: self.format,
+            direction: self.direction,
+            input_path: self.input,
+            max_depth: self.max_depth,
+            expand_dispatch: self.expand_dispatch,
+            external: self.external,
+            filter: self.filter,
+            filter_mode: self.filter_mode,
+            path_to: self.path_to,
+            path_mode: self.path_mode,
+            max_paths: self.max_paths,
+            report: self.report,
+            report_format: self.report_format,
+            roots: self.roots,
+            rules_path: self.rules,
+            diff_against: self.diff_against,
+            collapse: self.collapse,
+            module_depth: self.module_depth,
+            baseline_path: self.baseline,
+            baseline_scope: self.baseline_scope,
+            update_baseline: self.update_baseline,
+            impact,
+            sort_by: self.sort_by.unwrap_or(Metric::Betweenness),
+            top: self.top.unwrap_or(DEFAULT_TOP),
+            scale_nodes: self.scale_nodes,
         })
     }
 }
@@ -102,4 +615,545 @@ mod tests {
             "error should say 'does not exist'"
         );
     }
+
+    #[test]
+    fn into_config_rejects_callers_direction_without_entry() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "--direction",
+            "callers",
+        ]);
+        let result = cli.into_config();
+        assert!(result.is_err());
+        assert!(
+            result.unwrap_err().to_string().contains("entry function"),
+            "error should mention the missing entry function"
+        );
+
+        let input = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--direction",
+            "callers",
+            ".",
+            "main",
+        ]);
+        assert!(input.into_config().is_err());
+    }
+
+    #[test]
+    fn into_config_skips_workspace_validation_with_input() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "graph.json",
+            "/nonexistent/workspace/path/should_not_exist_abc123",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.input_path.as_deref(), Some("graph.json"));
+    }
+
+    #[test]
+    fn into_config_defaults_to_callees_direction() {
+        let cli = Cli::parse_from(["gen_callgraph", env!("CARGO_MANIFEST_DIR"), "main"]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.direction, Direction::Callees);
+    }
+
+    #[test]
+    fn into_config_carries_max_depth() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--max-depth",
+            "2",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.max_depth, Some(2));
+
+        let input = Cli::parse_from(["gen_callgraph", "--input", "g.json", "--max-depth", "2"]);
+        assert!(input.into_config().is_err());
+    }
+
+    #[test]
+    fn into_config_collects_repeated_filter_patterns() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--exclude-file",
+            "src/lsp/**",
+            "--exclude-file",
+            "tests/**",
+            "--exclude-name",
+            "::fmt$",
+            "--filter-mode",
+            "reconnect",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.filter.exclude_files, vec!["src/lsp/**", "tests/**"]);
+        assert_eq!(config.filter.exclude_names, vec!["::fmt$"]);
+        assert!(config.filter.include_groups.is_empty());
+        assert_eq!(config.filter_mode, FilterMode::Reconnect);
+    }
+
+    #[test]
+    fn into_config_requires_reconnect_to_filter_loaded_graph() {
+        let traverse = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--exclude-name",
+            "::fmt$",
+        ]);
+        let err = traverse.into_config().unwrap_err().to_string();
+        assert!(err.contains("--filter-mode reconnect"), "got: {err}");
+
+        let reconnect = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--exclude-name",
+            "::fmt$",
+            "--filter-mode",
+            "reconnect",
+        ]);
+        assert!(reconnect.into_config().is_ok());
+        let unfiltered = Cli::parse_from(["gen_callgraph", "--input", "g.json"]);
+        assert!(unfiltered.into_config().is_ok());
+    }
+
+    #[test]
+    fn into_config_rejects_path_query_without_entry() {
+        let cli = Cli::parse_from(["gen_callgraph", "--input", "graph.json", "--path-to", "foo"]);
+        let err = cli.into_config().unwrap_err().to_string();
+        assert!(err.contains("path source"), "got: {err}");
+    }
+
+    #[test]
+    fn into_config_carries_path_query() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--path-to",
+            "foo",
+            "--path-mode",
+            "all",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.path_to.as_deref(), Some("foo"));
+        assert_eq!(config.path_mode, PathMode::All);
+        assert_eq!(config.max_paths, 10);
+    }
+
+    #[test]
+    fn into_config_writes_graph_only_when_requested_with_report() {
+        let plain = Cli::parse_from(["gen_callgraph", env!("CARGO_MANIFEST_DIR")]);
+        assert_eq!(
+            plain.into_config().unwrap().output_path.as_deref(),
+            Some(DEFAULT_OUTPUT_PATH)
+        );
+
+        let report_only = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "--report",
+            "cycles",
+        ]);
+        let config = report_only.into_config().unwrap();
+        assert_eq!(config.report, Some(Report::Cycles));
+        assert_eq!(config.output_path, None);
+
+        let both = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "out.dot",
+            "--report",
+            "cycles",
+        ]);
+        assert_eq!(
+            both.into_config().unwrap().output_path.as_deref(),
+            Some("out.dot")
+        );
+    }
+
+    #[test]
+    fn into_config_parses_dead_code_roots() {
+        let default = Cli::parse_from(["gen_callgraph", env!("CARGO_MANIFEST_DIR")]);
+        assert_eq!(
+            default.into_config().unwrap().roots,
+            vec![RootKind::Main, RootKind::Tests]
+        );
+
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "--report",
+            "dead-code",
+            "--roots",
+            "pub,tests",
+            "--report-format",
+            "json",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.report, Some(Report::DeadCode));
+        assert_eq!(config.roots, vec![RootKind::Pub, RootKind::Tests]);
+        assert_eq!(config.report_format, ReportFormat::Json);
+    }
+
+    #[test]
+    fn into_config_rejects_depth_limit_with_reachability_reports() {
+        for report in ["dead-code", "tests"] {
+            let cli = Cli::parse_from([
+                "gen_callgraph",
+                env!("CARGO_MANIFEST_DIR"),
+                "--report",
+                report,
+                "--max-depth",
+                "2",
+            ]);
+            assert!(cli.into_config().is_err(), "{report} with --max-depth");
+        }
+    }
+
+    #[test]
+    fn into_config_merges_positional_and_repeated_entries() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--entry",
+            "handle_request",
+            "--entry",
+            "Server::start",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(
+            config.entry_functions,
+            vec!["main", "handle_request", "Server::start"]
+        );
+
+        let empty = Cli::parse_from(["gen_callgraph", env!("CARGO_MANIFEST_DIR"), ""]);
+        assert!(empty.into_config().unwrap().entry_functions.is_empty());
+    }
+
+    #[test]
+    fn into_config_validates_collapse_options() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "graph.json",
+            "--collapse",
+            "module",
+            "--module-depth",
+            "1",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.collapse, Some(CollapseBy::Module));
+        assert_eq!(config.module_depth, Some(1));
+
+        let zero = Cli::try_parse_from([
+            "gen_callgraph",
+            "--input",
+            "graph.json",
+            "--collapse",
+            "module",
+            "--module-depth",
+            "0",
+        ]);
+        assert!(zero.is_err());
+
+        let by_group = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "graph.json",
+            "--collapse",
+            "group",
+            "--module-depth",
+            "1",
+        ]);
+        let err = by_group.into_config().unwrap_err().to_string();
+        assert!(err.contains("--collapse module"), "got: {err}");
+
+        let dead_code = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "graph.json",
+            "--collapse",
+            "group",
+            "--report",
+            "dead-code",
+        ]);
+        assert!(dead_code.into_config().is_err());
+    }
+
+    #[test]
+    fn into_config_requires_rules_with_layering_report() {
+        let without_rules =
+            Cli::parse_from(["gen_callgraph", "--input", "g.json", "--report", "layering"]);
+        assert!(without_rules.into_config().is_err());
+
+        let stray_rules =
+            Cli::parse_from(["gen_callgraph", "--input", "g.json", "--rules", "a.toml"]);
+        assert!(stray_rules.into_config().is_err());
+
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--report",
+            "layering",
+            "--rules",
+            "a.toml",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.report, Some(Report::Layering));
+        assert_eq!(config.rules_path.as_deref(), Some("a.toml"));
+    }
+
+    #[test]
+    fn into_config_validates_tests_report() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "parse",
+            "--report",
+            "tests",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.report, Some(Report::Tests));
+        assert_eq!(config.entry_functions, vec!["parse"]);
+
+        let as_sarif = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--report",
+            "tests",
+            "--report-format",
+            "sarif",
+        ]);
+        assert!(as_sarif.into_config().is_err());
+
+        let with_path = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--path-to",
+            "parse",
+            "--report",
+            "tests",
+        ]);
+        assert!(with_path.into_config().is_err());
+    }
+
+    #[test]
+    fn into_config_validates_stats_options() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--report",
+            "stats",
+            "--sort-by",
+            "fan-in",
+            "--top",
+            "5",
+            "--report-format",
+            "csv",
+            "--scale-nodes",
+            "reach",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.report, Some(Report::Stats));
+        assert_eq!((config.sort_by, config.top), (Metric::FanIn, 5));
+        assert_eq!(config.scale_nodes, Some(Metric::Reach));
+
+        let csv_elsewhere = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--report",
+            "cycles",
+            "--report-format",
+            "csv",
+        ]);
+        assert!(csv_elsewhere.into_config().is_err());
+
+        let stray_top = Cli::parse_from(["gen_callgraph", "--input", "g.json", "--top", "5"]);
+        assert!(stray_top.into_config().is_err());
+
+        let stray_defaults = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--sort-by",
+            "betweenness",
+            "--top",
+            "20",
+        ]);
+        assert!(stray_defaults.into_config().is_err());
+
+        let sarif_alone = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--report-format",
+            "sarif",
+        ]);
+        assert!(sarif_alone.into_config().is_err());
+
+        let scaled = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--scale-nodes",
+            "fan-in",
+        ]);
+        assert!(scaled.into_config().is_ok());
+    }
+
+    #[test]
+    fn into_config_rejects_dispatch_expansion_without_callee_traversal() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--expand-dispatch",
+        ]);
+        assert!(cli.into_config().unwrap().expand_dispatch);
+
+        let callers = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--direction",
+            "callers",
+            "--expand-dispatch",
+        ]);
+        assert!(callers.into_config().is_err());
+
+        let input = Cli::parse_from(["gen_callgraph", "--input", "g.json", "--expand-dispatch"]);
+        assert!(input.into_config().is_err());
+    }
+
+    #[test]
+    fn into_config_parses_external_kinds_for_callee_traversal() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--external",
+            "std,deps",
+        ]);
+        assert_eq!(
+            cli.into_config().unwrap().external,
+            vec![ExternalCallees::Std, ExternalCallees::Deps]
+        );
+
+        let callers = Cli::parse_from([
+            "gen_callgraph",
+            env!("CARGO_MANIFEST_DIR"),
+            "main",
+            "--direction",
+            "callers",
+            "--external",
+            "deps",
+        ]);
+        assert!(callers.into_config().is_err());
+
+        let input = Cli::parse_from(["gen_callgraph", "--input", "g.json", "--external", "std"]);
+        assert!(input.into_config().is_err());
+    }
+
+    #[test]
+    fn into_config_validates_diff_options() {
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "new.json",
+            "--diff-against",
+            "old.json",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.diff_against.as_deref(), Some("old.json"));
+        assert_eq!(config.output_path.as_deref(), Some(DEFAULT_OUTPUT_PATH));
+
+        let with_report = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "new.json",
+            "--diff-against",
+            "old.json",
+            "--report",
+            "cycles",
+        ]);
+        assert!(with_report.into_config().is_err());
+    }
+
+    #[test]
+    fn into_config_validates_baseline_options() {
+        let without_baseline =
+            Cli::parse_from(["gen_callgraph", "--input", "g.json", "--report", "baseline"]);
+        assert!(without_baseline.into_config().is_err());
+
+        let stray_update =
+            Cli::parse_from(["gen_callgraph", "--input", "g.json", "--update-baseline"]);
+        assert!(stray_update.into_config().is_err());
+
+        let cli = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--report",
+            "baseline",
+            "--baseline",
+            "deps.json",
+            "--baseline-scope",
+            "cross-group",
+            "--update-baseline",
+        ]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(config.baseline_path.as_deref(), Some("deps.json"));
+        assert_eq!(config.baseline_scope, BaselineScope::CrossGroup);
+        assert!(config.update_baseline);
+        assert_eq!(config.output_path, None);
+    }
+
+    #[test]
+    fn into_config_validates_impact_options() {
+        let cli = Cli::parse_from(["gen_callgraph", ".", "", "--impact-revs", "main..HEAD"]);
+        let config = cli.into_config().unwrap();
+        assert_eq!(
+            config.impact,
+            Some(ImpactSource::Revisions(String::from("main..HEAD")))
+        );
+
+        let with_entry =
+            Cli::parse_from(["gen_callgraph", ".", "main", "--impact-patch", "a.diff"]);
+        assert!(with_entry.into_config().is_err());
+
+        let with_input = Cli::parse_from([
+            "gen_callgraph",
+            "--input",
+            "g.json",
+            "--impact-patch",
+            "a.diff",
+        ]);
+        assert!(with_input.into_config().is_err());
+
+        let both = Cli::try_parse_from([
+            "gen_callgraph",
+            "--impact-patch",
+            "a.diff",
+            "--impact-revs",
+            "HEAD",
+        ]);
+        assert!(both.is_err());
+    }
 }
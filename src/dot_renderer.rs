@@ -3,12 +3,42 @@
 //! No LSP or analysis knowledge; this module depends only on `CallGraph`. The output is a
 //! `digraph` with:
 //! - One `subgraph cluster_*` per group (rendered left-to-right via `rankdir=LR`)
-//! - Labelled nodes inside each cluster
-//! - Directed edges (`from -> to`) outside the clusters
+//! - Labelled nodes inside each cluster. Nodes with known metadata carry a `tooltip` with
+//!   their definition span, signature and flags; deprecated functions are drawn in gray and
+//!   nodes cut off by a depth limit (`truncated`) are drawn dashed. Entry functions
+//!   (`is_root`) get a double outline, and functions outside the workspace (`external`) a
+//!   box shape. Nodes with a `size` are drawn with a larger font
+//!   and width, up to [`MAX_SCALE`] times the default.
+//! - Directed edges (`from -> to`) outside the clusters. Edges with more than one call site
+//!   are labelled with the call count, and every edge with known call sites carries a
+//!   `tooltip` listing them as `file:line:column`. Dispatch edges, from a trait method to an
+//!   implementation, are dashed with a hollow arrowhead.
+//! - Nodes and edges carrying a [`Highlight`] drawn in its color (see [`highlight_color`]).
 
-use crate::call_graph::{CallGraph, CallGraphNode};
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode, FunctionKind, Highlight};
+use crate::renderer::Renderer;
 use std::collections::BTreeMap;
 
+/// [`Renderer`] registration for the GraphViz DOT format.
+pub struct DotRenderer;
+
+impl Renderer for DotRenderer {
+    fn name(&self) -> &'static str {
+        "dot"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dot", "gv"]
+    }
+
+    fn render(&self, graph: &CallGraph) -> String {
+        to_dot(graph)
+    }
+}
+
+/// Factor by which a node of `size` 1.0 is larger than an unscaled node.
+const MAX_SCALE: f64 = 2.5;
+
 fn escape_dot(value: &str) -> String {
     value
         .replace('\\', "\\\\")
@@ -16,6 +46,112 @@ fn escape_dot(value: &str) -> String {
         .replace('\n', "\\n")
 }
 
+/// GraphViz color used for highlighted nodes and edges.
+fn highlight_color(highlight: Highlight) -> &'static str {
+    match highlight {
+        Highlight::Cycle => "red",
+        Highlight::Unreached => "orange",
+        Highlight::Violation => "purple",
+        Highlight::Added => "green",
+        Highlight::Removed => "red",
+        Highlight::Changed => "blue",
+    }
+}
+
+/// Builds the `[...]` attribute list for a node: its label plus any metadata worth showing.
+fn node_attributes(node: &CallGraphNode, label: &str) -> String {
+    let mut attrs = vec![format!("label=\"{}\"", escape_dot(label))];
+
+    let mut tooltip = Vec::new();
+    if let Some(location) = &node.location {
+        tooltip.push(format!(
+            "{}:{}-{}",
+            location.file, location.start_line, location.end_line
+        ));
+    }
+    if let Some(detail) = &node.detail {
+        tooltip.push(detail.clone());
+    }
+    let mut flags = Vec::new();
+    if node.kind == FunctionKind::Method {
+        flags.push("method");
+    }
+    if node.is_test {
+        flags.push("test");
+    }
+    if node.deprecated {
+        flags.push("deprecated");
+    }
+    if node.truncated {
+        flags.push("truncated");
+    }
+    if node.external {
+        flags.push("external");
+    }
+    if !flags.is_empty() {
+        tooltip.push(format!("({})", flags.join(", ")));
+    }
+    if !tooltip.is_empty() {
+        attrs.push(format!("tooltip=\"{}\"", escape_dot(&tooltip.join("\n"))));
+    }
+
+    if node.deprecated {
+        attrs.push(String::from("fontcolor=gray"));
+    }
+    if node.truncated {
+        attrs.push(String::from("style=dashed"));
+    }
+    if node.is_root {
+        attrs.push(String::from("peripheries=2"));
+    }
+    if node.external {
+        attrs.push(String::from("shape=box"));
+    }
+    if let Some(highlight) = node.highlight {
+        attrs.push(format!("color={}", highlight_color(highlight)));
+    }
+    if let Some(size) = node.size {
+        // GraphViz defaults: 14pt font, 0.75in minimum width.
+        let scale = 1.0 + (MAX_SCALE - 1.0) * size.clamp(0.0, 1.0);
+        attrs.push(format!("fontsize={:.1}", 14.0 * scale));
+        attrs.push(format!("width={:.2}", 0.75 * scale));
+    }
+    format!("[{}]", attrs.join(", "))
+}
+
+/// Builds the `[...]` attribute list for an edge, or an empty string when the edge has no
+/// call-site information or highlight to show.
+fn edge_attributes(edge: &CallGraphEdge) -> String {
+    let mut attrs = Vec::new();
+    if edge.call_count() > 1 {
+        attrs.push(format!("label=\"{}x\"", edge.call_count()));
+    }
+    if !edge.call_sites.is_empty() {
+        let sites: Vec<String> = edge
+            .call_sites
+            .iter()
+            .map(|site| format!("{}:{}:{}", site.file, site.line, site.column))
+            .collect();
+        attrs.push(format!("tooltip=\"{}\"", escape_dot(&sites.join("\n"))));
+    }
+    if edge.dispatch {
+        attrs.push(String::from("style=dashed"));
+        attrs.push(String::from("arrowhead=empty"));
+        if edge.call_sites.is_empty() {
+            attrs.push(String::from("tooltip=\"may dispatch to\""));
+        }
+    }
+    if let Some(highlight) = edge.highlight {
+        attrs.push(format!("color={}", highlight_color(highlight)));
+        attrs.push(String::from("penwidth=2"));
+    }
+    if attrs.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attrs.join(", "))
+    }
+}
+
 pub fn to_dot(graph: &CallGraph) -> String {
     let mut out = String::from("digraph callgraph {\n");
     out.push_str("  rankdir=LR;\n");
@@ -37,11 +173,10 @@ pub fn to_dot(graph: &CallGraph) -> String {
         for node in nodes {
             // The subgraph cluster already shows the container/module via its label,
             // so display only the bare function/method name here.
-            let short_label = node.label.rsplit("::").next().unwrap_or(&node.label);
             out.push_str(&format!(
-                "    \"{}\" [label=\"{}\"];\n",
+                "    \"{}\" {};\n",
                 escape_dot(&node.id),
-                escape_dot(short_label)
+                node_attributes(node, node.short_label())
             ));
         }
         out.push_str("  }\n");
@@ -49,9 +184,10 @@ pub fn to_dot(graph: &CallGraph) -> String {
 
     for edge in &graph.edges {
         out.push_str(&format!(
-            "  \"{}\" -> \"{}\";\n",
+            "  \"{}\" -> \"{}\"{};\n",
             escape_dot(&edge.from),
-            escape_dot(&edge.to)
+            escape_dot(&edge.to),
+            edge_attributes(edge)
         ));
     }
 
@@ -62,26 +198,17 @@ pub fn to_dot(graph: &CallGraph) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::call_graph::{CallGraphEdge, CallGraphNode};
+    use crate::call_graph::testing::{graph, location, node};
+    use crate::call_graph::CallSite;
 
     fn make_graph(nodes: Vec<(&str, &str, &str)>, edges: Vec<(&str, &str)>) -> CallGraph {
-        CallGraph {
-            nodes: nodes
-                .into_iter()
-                .map(|(id, label, group)| CallGraphNode {
-                    id: id.to_string(),
-                    label: label.to_string(),
-                    group: group.to_string(),
-                })
-                .collect(),
-            edges: edges
+        graph(
+            nodes
                 .into_iter()
-                .map(|(from, to)| CallGraphEdge {
-                    from: from.to_string(),
-                    to: to.to_string(),
-                })
+                .map(|(id, label, group)| node(id, label, group))
                 .collect(),
-        }
+            &edges,
+        )
     }
 
     #[test]
@@ -130,4 +257,131 @@ mod tests {
             "edge should use node ids, got:\n{dot}"
         );
     }
+
+    #[test]
+    fn edge_with_multiple_call_sites_shows_count_and_locations() {
+        let mut graph = make_graph(
+            vec![("id1", "A::foo", "A"), ("id2", "B::bar", "B")],
+            vec![("id1", "id2")],
+        );
+        graph.edges[0].call_sites = vec![
+            CallSite {
+                file: "src/a.rs".to_string(),
+                line: 3,
+                column: 9,
+            },
+            CallSite {
+                file: "src/a.rs".to_string(),
+                line: 7,
+                column: 5,
+            },
+        ];
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains(
+                "\"id1\" -> \"id2\" [label=\"2x\", tooltip=\"src/a.rs:3:9\\nsrc/a.rs:7:5\"];"
+            ),
+            "edge should carry call count and call sites, got:\n{dot}"
+        );
+    }
+
+    #[test]
+    fn node_metadata_is_rendered_as_tooltip() {
+        let mut graph = make_graph(vec![("id1", "App::run", "App")], vec![]);
+        graph.nodes[0].location = location("src/app.rs", 13, 41);
+        graph.nodes[0].detail = Some("pub fn run()".to_string());
+        graph.nodes[0].kind = FunctionKind::Method;
+        graph.nodes[0].deprecated = true;
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains(
+                "[label=\"run\", tooltip=\"src/app.rs:13-41\\npub fn run()\\n(method, deprecated)\", fontcolor=gray]"
+            ),
+            "node should carry metadata tooltip, got:\n{dot}"
+        );
+    }
+
+    #[test]
+    fn truncated_node_is_dashed() {
+        let mut graph = make_graph(vec![("id1", "A::foo", "A")], vec![]);
+        graph.nodes[0].truncated = true;
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains("[label=\"foo\", tooltip=\"(truncated)\", style=dashed]"),
+            "truncated node should be dashed, got:\n{dot}"
+        );
+    }
+
+    #[test]
+    fn root_node_has_double_outline() {
+        let mut graph = make_graph(vec![("id1", "main", "functions")], vec![]);
+        graph.nodes[0].is_root = true;
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains("[label=\"main\", peripheries=2]"),
+            "root node should have a double outline, got:\n{dot}"
+        );
+    }
+
+    #[test]
+    fn external_node_is_boxed() {
+        let mut graph = make_graph(vec![("id1", "Vec::push", "alloc")], vec![]);
+        graph.nodes[0].external = true;
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains("[label=\"push\", tooltip=\"(external)\", shape=box]"),
+            "external node should be boxed, got:\n{dot}"
+        );
+    }
+
+    #[test]
+    fn highlighted_nodes_and_edges_are_colored() {
+        let mut graph = make_graph(vec![("id1", "A::walk", "A")], vec![("id1", "id1")]);
+        graph.nodes[0].highlight = Some(Highlight::Cycle);
+        graph.edges[0].highlight = Some(Highlight::Cycle);
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains("\"id1\" [label=\"walk\", color=red];"),
+            "highlighted node should be red, got:\n{dot}"
+        );
+        assert!(
+            dot.contains("\"id1\" -> \"id1\" [color=red, penwidth=2];"),
+            "highlighted edge should be red and bold, got:\n{dot}"
+        );
+    }
+
+    #[test]
+    fn sized_nodes_are_scaled() {
+        let mut graph = make_graph(vec![("id1", "hub", "app"), ("id2", "leaf", "app")], vec![]);
+        graph.nodes[0].size = Some(1.0);
+        graph.nodes[1].size = Some(0.0);
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains("\"id1\" [label=\"hub\", fontsize=35.0, width=1.88];"),
+            "largest node should be scaled up, got:\n{dot}"
+        );
+        assert!(
+            dot.contains("\"id2\" [label=\"leaf\", fontsize=14.0, width=0.75];"),
+            "smallest node should keep the default size, got:\n{dot}"
+        );
+    }
+
+    #[test]
+    fn dispatch_edges_are_dashed() {
+        let mut graph = make_graph(
+            vec![
+                ("t", "Transport::send", "Transport"),
+                ("i", "Stdio::send", "Stdio"),
+            ],
+            vec![("t", "i")],
+        );
+        graph.edges[0].dispatch = true;
+        let dot = to_dot(&graph);
+        assert!(
+            dot.contains(
+                "\"t\" -> \"i\" [style=dashed, arrowhead=empty, tooltip=\"may dispatch to\"];"
+            ),
+            "dispatch edge should be dashed, got:\n{dot}"
+        );
+    }
 }
@@ -6,6 +6,8 @@
 //! CallGraphError (top-level, wraps everything below)
 //! ├── LspError     — LSP communication failures
 //! ├── SymbolError  — symbol resolution failures
+//! ├── FilterError  — invalid include/exclude patterns
+//! ├── RulesError   — invalid architecture rules file
 //! ├── Io           — std::io::Error
 //! └── Other        — anyhow catch-all
 //! ```
@@ -23,6 +25,14 @@ pub enum CallGraphError {
     #[error("Symbol error: {0}")]
     Symbol(#[from] SymbolError),
 
+    /// Include/exclude filter errors
+    #[error("Filter error: {0}")]
+    Filter(#[from] FilterError),
+
+    /// Architecture rules file errors
+    #[error("Rules error: {0}")]
+    Rules(#[from] RulesError),
+
     /// File I/O errors
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -76,10 +86,29 @@ pub enum LspError {
 #[derive(Error, Debug)]
 pub enum SymbolError {
     /// The named entry function does not appear in `workspace/symbol` results after all
-    /// retry attempts. Check spelling, ensure the function exists in the workspace, and
-    /// allow time for rust-analyzer indexing to complete.
-    #[error("Entry function '{name}' not found in workspace")]
-    EntryFunctionNotFound { name: String },
+    /// retry attempts. `suggestions` holds the closest workspace function names, if any.
+    /// Check spelling, ensure the function exists in the workspace, and allow time for
+    /// rust-analyzer indexing to complete.
+    #[error(
+        "Entry function '{name}' not found in workspace{}",
+        did_you_mean(suggestions)
+    )]
+    EntryFunctionNotFound {
+        name: String,
+        suggestions: Vec<String>,
+    },
+
+    /// The entry spec names more than one workspace function. Qualify it with its type or
+    /// module (`MyStruct::new`, `crate::lsp::LspClient::new`) or give a location
+    /// (`src/app.rs:13`).
+    #[error(
+        "Entry function '{name}' is ambiguous; candidates:\n  {}",
+        candidates.join("\n  ")
+    )]
+    AmbiguousEntryFunction {
+        name: String,
+        candidates: Vec<String>,
+    },
 
     /// The function was found but `textDocument/prepareCallHierarchy` returned an empty
     /// list. The function may have no outgoing calls, or rust-analyzer does not support
@@ -96,6 +125,44 @@ pub enum SymbolError {
     },
 }
 
+/// Include/exclude filter errors
+#[derive(Error, Debug)]
+pub enum FilterError {
+    /// A `--include-*` / `--exclude-*` pattern could not be compiled. File patterns use glob
+    /// syntax (`src/lsp/**`); name patterns use regex syntax (`::fmt$`).
+    #[error("invalid {kind} '{pattern}': {reason}")]
+    InvalidPattern {
+        kind: &'static str,
+        pattern: String,
+        reason: String,
+    },
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!("; did you mean: {}?", suggestions.join(", "))
+    }
+}
+
+/// Architecture rules file errors
+#[derive(Error, Debug)]
+pub enum RulesError {
+    /// The file is not valid TOML or has unknown keys. The format is documented in
+    /// `analysis::layering`.
+    #[error("invalid rules file: {reason}")]
+    Parse { reason: String },
+
+    /// A layer or rule uses an empty module pattern. Use `*` to match every module.
+    #[error("module patterns must not be empty (use '*' for every module)")]
+    EmptyPattern,
+
+    /// The same module pattern is listed in two layers, so its layer is ambiguous.
+    #[error("module '{module}' is listed in more than one layer")]
+    DuplicateLayerModule { module: String },
+}
+
 impl CallGraphError {
     /// Create a call graph error with a custom message
     pub fn call_graph<S: Into<String>>(message: S) -> Self {
@@ -111,6 +178,7 @@ mod tests {
     fn test_error_display() {
         let err = SymbolError::EntryFunctionNotFound {
             name: "main".to_string(),
+            suggestions: vec![],
         };
         assert_eq!(
             err.to_string(),
@@ -118,6 +186,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_entry_not_found_lists_suggestions() {
+        let err = SymbolError::EntryFunctionNotFound {
+            name: "intialize".to_string(),
+            suggestions: vec!["initialize".to_string(), "initialized".to_string()],
+        };
+        assert_eq!(
+            err.to_string(),
+            "Entry function 'intialize' not found in workspace; did you mean: initialize, initialized?"
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_entry_lists_candidates() {
+        let err = SymbolError::AmbiguousEntryFunction {
+            name: "new".to_string(),
+            candidates: vec![
+                "app::App::new (src/app.rs:10)".to_string(),
+                "lsp::LspClient::new (src/lsp.rs:42)".to_string(),
+            ],
+        };
+        assert_eq!(
+            err.to_string(),
+            "Entry function 'new' is ambiguous; candidates:\n  app::App::new (src/app.rs:10)\n  lsp::LspClient::new (src/lsp.rs:42)"
+        );
+    }
+
     #[test]
     fn test_lsp_timeout_error() {
         let err = LspError::Timeout {
@@ -130,6 +225,7 @@ mod tests {
     fn test_error_conversion() {
         let symbol_err = SymbolError::EntryFunctionNotFound {
             name: "test".to_string(),
+            suggestions: vec![],
         };
         let call_graph_err: CallGraphError = symbol_err.into();
         // anyhow automatically converts any std::error::Error
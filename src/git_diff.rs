@@ -0,0 +1,285 @@
+//! Changed lines of a unified diff, read from a patch file or from `git diff`.
+//!
+//! Only the new side of the diff matters: line numbers refer to the files as they are after
+//! the change, which is what rust-analyzer sees in the workspace. This module knows nothing
+//! about functions; the builder maps the lines onto them.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Lines changed in one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path of the file after the change, relative to the workspace root. Patch files must
+    /// use such paths too, e.g. made by `git diff --relative` run in the workspace.
+    pub path: String,
+    /// 1-based inclusive line ranges in the new file, in ascending order. A removal is
+    /// recorded as the line where the following content now starts.
+    pub ranges: Vec<(u32, u32)>,
+}
+
+/// Parses a unified diff (as produced by `git diff` or `diff -u`) into the changed lines
+/// of every file that still exists after the change. Context lines are not counted. Paths
+/// are taken as they are, minus a `b/` prefix, so they must be relative to the workspace.
+pub fn parse_unified_diff(text: &str) -> Vec<FileChange> {
+    let mut changes: Vec<FileChange> = Vec::new();
+    let mut current: Option<FileChange> = None;
+    let mut hunk: Option<Hunk> = None;
+
+    for line in text.lines() {
+        if let Some(h) = hunk.as_mut().filter(|h| h.old_left > 0 || h.new_left > 0) {
+            if line.starts_with('+') {
+                push_line(&mut current, h.next_line);
+                h.next_line += 1;
+                h.new_left = h.new_left.saturating_sub(1);
+            } else if line.starts_with('-') {
+                // A removal touches the line the following content now starts on.
+                push_line(&mut current, h.next_line.max(1));
+                h.old_left = h.old_left.saturating_sub(1);
+            } else if !line.starts_with('\\') {
+                h.next_line += 1;
+                h.old_left = h.old_left.saturating_sub(1);
+                h.new_left = h.new_left.saturating_sub(1);
+            }
+            continue;
+        }
+
+        if let Some(path) = line.strip_prefix("+++ ") {
+            changes.extend(current.take().filter(|c| !c.ranges.is_empty()));
+            current = new_file_path(path).map(|path| FileChange {
+                path,
+                ranges: Vec::new(),
+            });
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            hunk = Hunk::parse(header);
+        }
+    }
+    changes.extend(current.filter(|c| !c.ranges.is_empty()));
+    changes
+}
+
+/// Position within a hunk: the next line number of the new side and the number of old and
+/// new lines still to come.
+#[derive(Debug, PartialEq, Eq)]
+struct Hunk {
+    next_line: u32,
+    old_left: u32,
+    new_left: u32,
+}
+
+impl Hunk {
+    /// Parses a hunk header such as `-10,2 +12,3 @@ fn run()`. A missing count means one
+    /// line. An empty new side (`+12,0`) starts after line 12.
+    fn parse(header: &str) -> Option<Self> {
+        let mut sides = header.split_whitespace();
+        let (_, old_left) = parse_range(sides.next()?.strip_prefix('-')?)?;
+        let (start, new_left) = parse_range(sides.next()?.strip_prefix('+')?)?;
+        Some(Hunk {
+            next_line: if new_left == 0 { start + 1 } else { start },
+            old_left,
+            new_left,
+        })
+    }
+}
+
+/// Parses `start,count` or `start` from a hunk header.
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Path after the change from a `+++` line, without the `b/` prefix git adds. `None` for
+/// deleted files.
+fn new_file_path(raw: &str) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or(raw).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix("b/").unwrap_or(path).to_string())
+}
+
+/// Adds `line` to the ranges of `change`, extending the last range when adjacent.
+fn push_line(change: &mut Option<FileChange>, line: u32) {
+    let Some(change) = change else {
+        return;
+    };
+    match change.ranges.last_mut() {
+        Some((_, end)) if line <= *end + 1 => *end = (*end).max(line),
+        _ => change.ranges.push((line, line)),
+    }
+}
+
+/// Runs `git diff` in `workspace` for `revisions`: `OLD..NEW` compares two revisions,
+/// `OLD...NEW` compares NEW with its merge base with OLD, and a single revision compares it
+/// with the working tree. Paths are relative to `workspace`, and files outside it are left
+/// out.
+///
+/// Line numbers must match the files on disk, so for a range NEW has to be the checked-out
+/// `HEAD` and the working tree must have no uncommitted changes.
+pub fn git_diff(workspace: &Path, revisions: &str) -> anyhow::Result<String> {
+    if let Some(new) = new_revision(revisions) {
+        let new_commit = git(
+            workspace,
+            &["rev-parse", "--verify", &format!("{}^{{commit}}", new)],
+        )?;
+        if new_commit != git(workspace, &["rev-parse", "--verify", "HEAD"])? {
+            return Err(anyhow::anyhow!(
+                "cannot diff {}: '{}' is not the checked-out HEAD, so its line numbers would \
+                 not match the files on disk; check it out first",
+                revisions,
+                new
+            ));
+        }
+        let status = git(
+            workspace,
+            &["status", "--porcelain", "--untracked-files=no", "--", "."],
+        )?;
+        if !status.is_empty() {
+            return Err(anyhow::anyhow!(
+                "cannot diff {}: the working tree has uncommitted changes; commit or stash \
+                 them, or give only OLD to compare with the working tree",
+                revisions
+            ));
+        }
+    }
+    git(
+        workspace,
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            // Fixed prefixes, whatever `diff.noprefix` or `diff.mnemonicPrefix` say: the
+            // parser strips `b/`.
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            "--relative",
+            "--unified=0",
+            revisions,
+        ],
+    )
+}
+
+/// NEW of `OLD..NEW` or `OLD...NEW`, where an empty side stands for `HEAD` as in git.
+/// `None` for a single revision.
+fn new_revision(revisions: &str) -> Option<&str> {
+    let (_, new) = revisions
+        .split_once("...")
+        .or_else(|| revisions.split_once(".."))?;
+    Some(if new.is_empty() { "HEAD" } else { new })
+}
+
+/// Runs git with `args` in `workspace` and returns its standard output, without the
+/// trailing newline.
+fn git(workspace: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(workspace)
+        .args(args)
+        .output()
+        .map_err(|e| anyhow::anyhow!("cannot run git: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "\
+diff --git a/src/app.rs b/src/app.rs
+index 1111111..2222222 100644
+--- a/src/app.rs
++++ b/src/app.rs
+@@ -10,3 +10,4 @@ pub async fn run(config: Config) {
+     let a = 1;
+-    let b = 2;
++    let b = 3;
++    let c = 4;
+     a + b
+@@ -40,2 +41,0 @@ fn helper() {
+-    old();
+-    older();
+diff --git a/src/gone.rs b/src/gone.rs
+deleted file mode 100644
+--- a/src/gone.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-fn gone() {
+-}
+diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1,2 @@
++fn fresh() {
++}
+";
+
+    #[test]
+    fn changed_lines_are_collected_per_new_file() {
+        let changes = parse_unified_diff(PATCH);
+        assert_eq!(
+            changes,
+            vec![
+                FileChange {
+                    path: String::from("src/app.rs"),
+                    ranges: vec![(11, 12), (42, 42)],
+                },
+                FileChange {
+                    path: String::from("src/new.rs"),
+                    ranges: vec![(1, 2)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_context_hunks_are_parsed() {
+        let patch =
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -3 +3 @@\n-a\n+b\n@@ -9,0 +10,2 @@\n+c\n+d\n";
+        let changes = parse_unified_diff(patch);
+        assert_eq!(changes[0].ranges, vec![(3, 3), (10, 11)]);
+    }
+
+    #[test]
+    fn plain_diff_without_git_headers_is_parsed() {
+        let patch = "--- a.rs\n+++ a.rs\n@@ -1,2 +1,2 @@\n-x\n+y\n z\n--- b.rs\n+++ b.rs\n@@ -5 +5 @@\n-u\n+v\n";
+        let changes = parse_unified_diff(patch);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].ranges, vec![(1, 1)]);
+        assert_eq!(changes[1].path, "b.rs");
+        assert_eq!(changes[1].ranges, vec![(5, 5)]);
+    }
+
+    #[test]
+    fn hunk_header_gives_new_side() {
+        let hunk = |next_line, old_left, new_left| Hunk {
+            next_line,
+            old_left,
+            new_left,
+        };
+        assert_eq!(Hunk::parse("-10,2 +12,3 @@ fn run()"), Some(hunk(12, 2, 3)));
+        assert_eq!(Hunk::parse("-1 +1 @@"), Some(hunk(1, 1, 1)));
+        assert_eq!(Hunk::parse("-40,2 +41,0 @@"), Some(hunk(42, 2, 0)));
+        assert_eq!(Hunk::parse("garbage"), None);
+    }
+
+    #[test]
+    fn new_revision_of_two_and_three_dot_ranges() {
+        assert_eq!(new_revision("main..HEAD"), Some("HEAD"));
+        assert_eq!(new_revision("v1.0...feature"), Some("feature"));
+        assert_eq!(new_revision("main..."), Some("HEAD"));
+        assert_eq!(new_revision("main.."), Some("HEAD"));
+        assert_eq!(new_revision("HEAD~3"), None);
+    }
+}
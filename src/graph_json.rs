@@ -0,0 +1,217 @@
+//! Serializes a [`CallGraph`](crate::call_graph::CallGraph) to JSON and loads it back.
+//!
+//! Like `dot_renderer`, this module depends only on `CallGraph`; it has no LSP or analysis
+//! knowledge. A saved graph can be reloaded with [`from_json`] so that rendering and analysis
+//! run without starting rust-analyzer.
+//!
+//! # Schema (version 1)
+//!
+//! ```text
+//! {
+//!   "schema_version": 1,
+//!   "nodes": [
+//!     {
+//!       "id": "...",                 // function path, `#n` for duplicates; see below
+//!       "label": "MyStruct::method", // qualified display label
+//!       "group": "MyStruct",         // cluster / container name
+//!       "location": { "file": "src/lib.rs", "start_line": 10, "end_line": 20 } | null,
+//!       "kind": "function" | "method",
+//!       "detail": "pub fn method(&self)" | null,
+//!       "deprecated": false,
+//!       "is_test": false,
+//!       "truncated": false,          // depth limit hid further calls from this node
+//!       "is_root": false,            // an entry function traversal started from
+//!       "external": false,           // outside the workspace; group is its crate
+//!       "highlight": "cycle" | null, // set by analyses, see below
+//!       "size": 0.4                  // optional: display size from 0 to 1 (`--scale-nodes`)
+//!     }
+//!   ],
+//!   "edges": [
+//!     {
+//!       "from": "...",               // caller node id
+//!       "to": "...",                 // callee node id
+//!       "call_sites": [ { "file": "src/lib.rs", "line": 12, "column": 9 } ],
+//!       "highlight": "cycle" | null,
+//!       "dispatch": false,           // trait method to an implementation it may dispatch to
+//!       "call_count": 1              // derived from call_sites; ignored on import
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Node `id`s are unique and referenced by edges. Graphs built from a workspace use the
+//! function's path as id (see `call_graph::identity`): crate, module path, `impl` block and
+//! name, as in `my_crate::lsp::LspClient::send`, with the block of a trait impl written
+//! `<Point as Display>`. Functions sharing a path get a `#n` suffix, numbered by position
+//! among the workspace functions with that path in their file (`my_crate::point::new#2`).
+//! External functions use their crate's path (`serde_json::de::from_str`). Ids stay the
+//! same when lines move, which `--baseline` and `--diff-against` rely on to match functions
+//! across saved graphs; `location` records where the function currently is.
+//!
+//! `highlight` marks nodes and edges an analysis singled out: `cycle` (part of a recursion
+//! cycle), `added` / `removed` (graph diff; `added` also marks calls missing from a
+//! baseline), for nodes only `unreached` (dead code) and `changed` (touched by the change of
+//! an impact analysis), and for edges only `violation` (a call breaking an architecture
+//! rule). Lines and columns are 1-based. Node metadata fields and `call_sites` may be
+//! omitted on import. Documents with a `schema_version` newer than [`SCHEMA_VERSION`] are
+//! rejected.
+
+use crate::call_graph::{CallGraph, CallGraphEdge, CallGraphNode};
+use crate::renderer::Renderer;
+use serde::{Deserialize, Serialize};
+
+/// Version of the JSON document layout written by [`to_json`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// [`Renderer`] registration for the JSON format.
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn render(&self, graph: &CallGraph) -> String {
+        to_json(graph)
+    }
+}
+
+#[derive(Serialize)]
+struct GraphDocumentOut<'a> {
+    schema_version: u32,
+    nodes: &'a [CallGraphNode],
+    edges: Vec<EdgeOut<'a>>,
+}
+
+#[derive(Serialize)]
+struct EdgeOut<'a> {
+    #[serde(flatten)]
+    edge: &'a CallGraphEdge,
+    call_count: usize,
+}
+
+#[derive(Deserialize)]
+struct GraphDocumentIn {
+    schema_version: u32,
+    #[serde(flatten)]
+    graph: CallGraph,
+}
+
+/// Serializes `graph` as a pretty-printed JSON document following the module-level schema.
+pub fn to_json(graph: &CallGraph) -> String {
+    let document = GraphDocumentOut {
+        schema_version: SCHEMA_VERSION,
+        nodes: &graph.nodes,
+        edges: graph
+            .edges
+            .iter()
+            .map(|edge| EdgeOut {
+                edge,
+                call_count: edge.call_count(),
+            })
+            .collect(),
+    };
+    let mut out = serde_json::to_string_pretty(&document)
+        .expect("CallGraph contains only JSON-representable data");
+    out.push('\n');
+    out
+}
+
+/// Parses a JSON document previously written by [`to_json`].
+///
+/// Fails when the text is not valid JSON, does not match the schema, or declares a
+/// `schema_version` this build does not understand.
+pub fn from_json(text: &str) -> anyhow::Result<CallGraph> {
+    let document: GraphDocumentIn = serde_json::from_str(text)
+        .map_err(|e| anyhow::anyhow!("invalid call graph JSON: {}", e))?;
+    if document.schema_version > SCHEMA_VERSION {
+        return Err(anyhow::anyhow!(
+            "call graph JSON has schema_version {}, but this build only supports up to {}",
+            document.schema_version,
+            SCHEMA_VERSION
+        ));
+    }
+    Ok(document.graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{edge, location, node};
+    use crate::call_graph::{CallSite, FunctionKind};
+
+    fn sample_graph() -> CallGraph {
+        CallGraph {
+            nodes: vec![
+                CallGraphNode {
+                    location: location("src/app.rs", 13, 41),
+                    kind: FunctionKind::Method,
+                    detail: Some("pub fn run(&self)".to_string()),
+                    is_root: true,
+                    ..node("a", "App::run", "App")
+                },
+                node("b", "helper", "functions"),
+            ],
+            edges: vec![CallGraphEdge {
+                call_sites: vec![CallSite {
+                    file: "src/app.rs".to_string(),
+                    line: 20,
+                    column: 9,
+                }],
+                ..edge("a", "b")
+            }],
+        }
+    }
+
+    #[test]
+    fn to_json_writes_schema_version_and_call_count() {
+        let value: serde_json::Value = serde_json::from_str(&to_json(&sample_graph())).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["nodes"][0]["kind"], "method");
+        assert_eq!(value["edges"][0]["call_count"], 1);
+        assert_eq!(value["edges"][0]["call_sites"][0]["line"], 20);
+    }
+
+    #[test]
+    fn round_trip_preserves_nodes_and_edges() {
+        let graph = from_json(&to_json(&sample_graph())).unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].label, "App::run");
+        assert_eq!(graph.nodes[0].location.as_ref().unwrap().start_line, 13);
+        assert_eq!(graph.nodes[0].kind, FunctionKind::Method);
+        assert!(graph.nodes[0].is_root);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].call_count(), 1);
+    }
+
+    #[test]
+    fn from_json_accepts_minimal_nodes_and_edges() {
+        let text = r#"{
+            "schema_version": 1,
+            "nodes": [{"id": "a", "label": "a", "group": "g"}],
+            "edges": [{"from": "a", "to": "a"}]
+        }"#;
+        let graph = from_json(text).unwrap();
+        assert_eq!(graph.nodes[0].kind, FunctionKind::Function);
+        assert!(graph.edges[0].call_sites.is_empty());
+    }
+
+    #[test]
+    fn from_json_rejects_newer_schema_version() {
+        let text = r#"{"schema_version": 999, "nodes": [], "edges": []}"#;
+        let err = from_json(text).unwrap_err();
+        assert!(
+            err.to_string().contains("schema_version 999"),
+            "error should mention the unsupported version, got: {err}"
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_malformed_text() {
+        assert!(from_json("not json").is_err());
+    }
+}
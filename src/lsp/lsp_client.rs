@@ -24,8 +24,8 @@ use crate::lsp::lsp_protocol::{FramedBox, FramedTransport};
 use crate::lsp::message_creator::MessageBuilder;
 use crate::lsp::types::{Message, Notification};
 use lsp_types::{
-    CallHierarchyItem, CallHierarchyOutgoingCall, DocumentSymbol, InitializeResult,
-    SymbolInformation,
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, DocumentSymbol,
+    GotoDefinitionResponse, InitializeResult, Location, Position, SymbolInformation,
 };
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -42,6 +42,8 @@ pub struct LspClient {
     #[allow(dead_code)]
     crate_name: String,
     request_timeout: Duration,
+    /// Set once the server has reported the end of workspace indexing.
+    indexing_complete: bool,
 }
 
 impl LspClient {
@@ -67,6 +69,7 @@ impl LspClient {
             workspace_root_path,
             crate_name,
             request_timeout: Duration::from_secs(10),
+            indexing_complete: false,
         }
     }
 
@@ -154,6 +157,53 @@ impl LspClient {
             .await
     }
 
+    /// Sends a `textDocument/prepareCallHierarchy` request for an arbitrary position.
+    /// Returns no items when there is no function at `position`.
+    pub(crate) async fn text_document_prepare_call_hierarchy_at(
+        &mut self,
+        uri: &lsp_types::Url,
+        position: Position,
+    ) -> Result<Vec<CallHierarchyItem>, LspError> {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "position": position
+        });
+
+        let items: Option<Vec<CallHierarchyItem>> = self
+            .request("textDocument/prepareCallHierarchy", params)
+            .await?;
+        Ok(items.unwrap_or_default())
+    }
+
+    /// Sends a `textDocument/implementation` request and returns the implementation
+    /// locations, pointing at their names when the server sends location links.
+    pub(crate) async fn text_document_implementation(
+        &mut self,
+        uri: &lsp_types::Url,
+        position: Position,
+    ) -> Result<Vec<Location>, LspError> {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "position": position
+        });
+
+        let response: Option<GotoDefinitionResponse> =
+            self.request("textDocument/implementation", params).await?;
+        Ok(match response {
+            None => Vec::new(),
+            Some(GotoDefinitionResponse::Scalar(location)) => vec![location],
+            Some(GotoDefinitionResponse::Array(locations)) => locations,
+            Some(GotoDefinitionResponse::Link(links)) => links
+                .into_iter()
+                .map(|link| Location::new(link.target_uri, link.target_selection_range))
+                .collect(),
+        })
+    }
+
     /// Sends a `callHierarchy/outgoingCalls` request.
     pub(crate) async fn call_hierarchy_outgoing_calls(
         &mut self,
@@ -166,6 +216,18 @@ impl LspClient {
         .await
     }
 
+    /// Sends a `callHierarchy/incomingCalls` request.
+    pub(crate) async fn call_hierarchy_incoming_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Result<Vec<CallHierarchyIncomingCall>, LspError> {
+        self.request(
+            "callHierarchy/incomingCalls",
+            serde_json::json!({"item": item}),
+        )
+        .await
+    }
+
     /// Sends a `textDocument/didOpen` notification.
     pub(crate) async fn text_document_did_open(
         &mut self,
@@ -273,6 +335,17 @@ impl LspClient {
         &self.crate_name
     }
 
+    /// Records that the server reported the end of workspace indexing, so symbol lookups
+    /// need not wait for results to appear.
+    pub(crate) fn mark_indexing_complete(&mut self) {
+        self.indexing_complete = true;
+    }
+
+    /// Returns `true` once [`Self::mark_indexing_complete`] has been called.
+    pub(crate) fn is_indexing_complete(&self) -> bool {
+        self.indexing_complete
+    }
+
     // -----------------------------------------------------------------------
     // Private helpers
     // -----------------------------------------------------------------------
@@ -281,7 +354,8 @@ impl LspClient {
     ///
     /// 1. Builds the request.
     /// 2. Sends it via the Protocol Layer and awaits the response.
-    /// 3. Matches the response variant and deserializes the result into `R`.
+    /// 3. Matches the response variant and deserializes the result into `R`. A missing or
+    ///    `null` result is accepted when `R` is an `Option`.
     async fn request<P, R>(&mut self, method: &str, params: P) -> Result<R, LspError>
     where
         P: serde::Serialize,
@@ -301,13 +375,15 @@ impl LspClient {
 
         match response {
             Message::Response(resp) => {
-                let result = resp.result.ok_or_else(|| LspError::InvalidResponse {
-                    method: method.to_string(),
-                    reason: String::from("response has no result"),
-                })?;
+                let missing = resp.result.is_none();
+                let result = resp.result.unwrap_or(serde_json::Value::Null);
                 serde_json::from_value(result).map_err(|e| LspError::InvalidResponse {
                     method: method.to_string(),
-                    reason: format!("failed to deserialize response: {}", e),
+                    reason: if missing {
+                        String::from("response has no result")
+                    } else {
+                        format!("failed to deserialize response: {}", e)
+                    },
                 })
             }
             Message::Error(error) => Err(LspError::RequestFailed {
@@ -25,6 +25,7 @@ pub struct MessageBuilder {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -116,6 +117,10 @@ impl MessageBuilder {
                     call_hierarchy: Some(lsp_types::CallHierarchyClientCapabilities {
                         dynamic_registration: Some(true),
                     }),
+                    implementation: Some(lsp_types::GotoCapability {
+                        dynamic_registration: Some(true),
+                        link_support: Some(true),
+                    }),
                     document_symbol: Some(lsp_types::DocumentSymbolClientCapabilities {
                         dynamic_registration: Some(true),
                         symbol_kind: Some(SymbolKindCapability {
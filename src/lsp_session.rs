@@ -40,7 +40,7 @@ impl LspSession {
 
         let mut client = lsp::LspClient::new(Box::new(stdio), config.workspace.clone());
         client.initialize().await?;
-        println!("Initialization Success");
+        eprintln!("Initialization Success");
 
         wait_for_indexing(&mut client).await?;
 
@@ -73,9 +73,10 @@ impl LspSession {
 /// and exits once all have been closed and no new notifications arrive for 500 ms.
 ///
 /// Falls back to a 3 s no-notification timeout (small projects that finish quickly)
-/// and a hard 120 s deadline in case the server never signals completion.
+/// and a hard 120 s deadline in case the server never signals completion. Only a
+/// completion signalled by the server marks the client's indexing as complete.
 async fn wait_for_indexing(client: &mut lsp::LspClient) -> anyhow::Result<()> {
-    println!("Waiting for rust-analyzer to index the workspace...");
+    eprintln!("Waiting for rust-analyzer to index the workspace...");
 
     let mut active_progress: u32 = 0;
     let mut seen_any_progress = false;
@@ -85,7 +86,7 @@ async fn wait_for_indexing(client: &mut lsp::LspClient) -> anyhow::Result<()> {
 
     loop {
         if std::time::Instant::now() >= deadline {
-            println!("  Timed out waiting for indexing (120 s), continuing");
+            eprintln!("  Timed out waiting for indexing (120 s), continuing");
             break;
         }
 
@@ -105,7 +106,7 @@ async fn wait_for_indexing(client: &mut lsp::LspClient) -> anyhow::Result<()> {
                             active_progress += 1;
                             seen_any_progress = true;
                             all_done_since = None;
-                            println!("  Indexing in progress (active: {})", active_progress);
+                            eprintln!("  Indexing in progress (active: {})", active_progress);
                         }
                         Some("end") if active_progress > 0 => {
                             active_progress -= 1;
@@ -122,14 +123,15 @@ async fn wait_for_indexing(client: &mut lsp::LspClient) -> anyhow::Result<()> {
                 if seen_any_progress {
                     if let Some(since) = all_done_since {
                         if since.elapsed() >= Duration::from_millis(500) {
-                            println!("  Indexing complete");
+                            eprintln!("  Indexing complete");
+                            client.mark_indexing_complete();
                             break;
                         }
                     }
                 }
                 // Fallback: no notifications at all for 3 s (e.g. very small project).
                 if last_notification_at.elapsed() >= Duration::from_secs(3) {
-                    println!("  No notifications for 3 s, assuming indexing complete");
+                    eprintln!("  No notifications for 3 s, assuming indexing complete");
                     break;
                 }
                 tokio::time::sleep(Duration::from_millis(50)).await;
@@ -10,6 +10,11 @@
 //! | `lsp_session` | LSP session lifecycle: spawn, initialize, indexing wait, shutdown. |
 //! | `call_graph_builder` | Builds `CallGraph` from LSP results. No output format knowledge. |
 //! | `dot_renderer` | Renders `CallGraph` into DOT format string. No LSP/analysis knowledge. |
+//! | `mermaid_renderer` | Renders `CallGraph` into a Mermaid flowchart string. No LSP/analysis knowledge. |
+//! | `git_diff` | Reads the changed lines of a patch file or `git diff`. No LSP knowledge. |
+//! | `graph_json` | Saves `CallGraph` as versioned JSON and loads it back. No LSP/analysis knowledge. |
+//! | `analysis` | Pure algorithms over a finished `CallGraph` (e.g. call paths). No LSP knowledge. |
+//! | `renderer` | `Renderer` trait and registry of output formats; selects one by name or file extension. |
 //! | `app` | Orchestration only. Wires CLI → LspSession → Builder → Renderer → file write. |
 //! | `main` | Entry point. Parses config and calls `app::run`. |
 //!
@@ -19,17 +24,22 @@
 //! CLI -> App -> LspSession -> LspClient
 //!          \-> CallGraphBuilder (borrows LspClient)
 //!                   |
-//!              DotRenderer (depends only on CallGraph)
+//!              Renderer: DOT / JSON / Mermaid (depends only on CallGraph)
 //! ```
 
+mod analysis;
 mod app;
 mod call_graph;
 mod call_graph_builder;
 mod cli;
 mod dot_renderer;
 mod error;
+mod git_diff;
+mod graph_json;
 mod lsp;
 mod lsp_session;
+mod mermaid_renderer;
+mod renderer;
 use cli::Cli;
 
 #[tokio::main]
@@ -0,0 +1,324 @@
+//! Renders a [`CallGraph`](crate::call_graph::CallGraph) into a Mermaid flowchart string.
+//!
+//! No LSP or analysis knowledge; this module depends only on `CallGraph`. The output is a
+//! `flowchart LR` with:
+//! - One `subgraph` per group, titled with the group name
+//! - Nodes labelled with the bare function/method name, as in the DOT output
+//! - Directed edges (`from --> to`), labelled with the call count when greater than one;
+//!   dispatch edges from a trait method to an implementation are dotted (`from -.-> to`)
+//! - A dashed `truncated` class applied to nodes cut off by a depth limit, a thick-bordered
+//!   `root` class applied to entry functions, and a gray `external` class applied to
+//!   functions outside the workspace
+//! - A class per [`Highlight`] for highlighted nodes, and `linkStyle` for highlighted edges
+//!
+//! Node ids produced by the builder embed URIs (`file:///...:line:col:name`), which Mermaid
+//! cannot use as identifiers. [`mangle_id`] maps them to `[A-Za-z0-9_]` identifiers
+//! injectively, so distinct ids never collide.
+
+use crate::call_graph::{CallGraph, CallGraphNode, Highlight};
+use crate::renderer::Renderer;
+use std::collections::BTreeMap;
+
+/// [`Renderer`] registration for Mermaid flowcharts.
+pub struct MermaidRenderer;
+
+impl Renderer for MermaidRenderer {
+    fn name(&self) -> &'static str {
+        "mermaid"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["mmd", "mermaid"]
+    }
+
+    fn render(&self, graph: &CallGraph) -> String {
+        to_mermaid(graph)
+    }
+}
+
+/// Maps an arbitrary node id to a Mermaid-safe identifier.
+///
+/// ASCII letters and digits are kept; every other byte (including `_`) is written as `_XX`
+/// in hex. The `n` prefix keeps the result from starting with a digit or matching a Mermaid
+/// keyword such as `end`.
+fn mangle_id(id: &str) -> String {
+    let mut out = String::from("n");
+    for byte in id.bytes() {
+        if byte.is_ascii_alphanumeric() {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("_{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Escapes text for use inside a quoted Mermaid label (`["..."]`).
+fn escape_label(value: &str) -> String {
+    value
+        .replace('&', "#amp;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', " ")
+}
+
+/// Class name and CSS-like style used for highlighted nodes and edges.
+fn highlight_style(highlight: Highlight) -> (&'static str, &'static str) {
+    match highlight {
+        Highlight::Cycle => ("cycle", "stroke:red,stroke-width:2px"),
+        Highlight::Unreached => ("unreached", "stroke:orange,stroke-width:2px"),
+        Highlight::Violation => ("violation", "stroke:purple,stroke-width:2px"),
+        Highlight::Added => ("added", "stroke:green,stroke-width:2px"),
+        Highlight::Removed => ("removed", "stroke:red,stroke-width:2px"),
+        Highlight::Changed => ("changed", "stroke:blue,stroke-width:2px"),
+    }
+}
+
+/// Defines `class` and applies it to `ids`; does nothing when `ids` is empty.
+fn push_class(out: &mut String, class: &str, style: &str, ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+    out.push_str(&format!("  classDef {} {}\n", class, style));
+    out.push_str(&format!("  class {} {}\n", ids.join(","), class));
+}
+
+pub fn to_mermaid(graph: &CallGraph) -> String {
+    let mut out = String::from("flowchart LR\n");
+
+    let mut grouped: BTreeMap<String, Vec<&CallGraphNode>> = BTreeMap::new();
+    for node in &graph.nodes {
+        grouped.entry(node.group.clone()).or_default().push(node);
+    }
+
+    for (idx, (group, nodes)) in grouped.iter().enumerate() {
+        out.push_str(&format!(
+            "  subgraph group_{}[\"{}\"]\n",
+            idx,
+            escape_label(group)
+        ));
+        for node in nodes {
+            // The subgraph title already shows the container/module, so display only the
+            // bare function/method name here.
+            out.push_str(&format!(
+                "    {}[\"{}\"]\n",
+                mangle_id(&node.id),
+                escape_label(node.short_label())
+            ));
+        }
+        out.push_str("  end\n");
+    }
+
+    for edge in &graph.edges {
+        let arrow = if edge.dispatch {
+            String::from("-.->")
+        } else if edge.call_count() > 1 {
+            format!("-->|\"{}x\"|", edge.call_count())
+        } else {
+            String::from("-->")
+        };
+        out.push_str(&format!(
+            "  {} {} {}\n",
+            mangle_id(&edge.from),
+            arrow,
+            mangle_id(&edge.to)
+        ));
+    }
+
+    let flagged = |applies: fn(&CallGraphNode) -> bool| -> Vec<String> {
+        graph
+            .nodes
+            .iter()
+            .filter(|node| applies(node))
+            .map(|node| mangle_id(&node.id))
+            .collect()
+    };
+    push_class(
+        &mut out,
+        "truncated",
+        "stroke-dasharray: 5 5",
+        &flagged(|n| n.truncated),
+    );
+    push_class(
+        &mut out,
+        "root",
+        "stroke-width:4px",
+        &flagged(|n| n.is_root),
+    );
+    push_class(
+        &mut out,
+        "external",
+        "fill:#eee,color:#555",
+        &flagged(|n| n.external),
+    );
+
+    let mut highlighted: BTreeMap<(&str, &str), Vec<String>> = BTreeMap::new();
+    for node in &graph.nodes {
+        if let Some(highlight) = node.highlight {
+            highlighted
+                .entry(highlight_style(highlight))
+                .or_default()
+                .push(mangle_id(&node.id));
+        }
+    }
+    for ((class, style), ids) in &highlighted {
+        push_class(&mut out, class, style, ids);
+    }
+    // `linkStyle` addresses edges by their position in the output.
+    for (index, edge) in graph.edges.iter().enumerate() {
+        if let Some(highlight) = edge.highlight {
+            out.push_str(&format!(
+                "  linkStyle {} {}\n",
+                index,
+                highlight_style(highlight).1
+            ));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::testing::{graph, node};
+    use crate::call_graph::CallSite;
+
+    fn make_graph(nodes: Vec<(&str, &str, &str)>, edges: Vec<(&str, &str)>) -> CallGraph {
+        graph(
+            nodes
+                .into_iter()
+                .map(|(id, label, group)| node(id, label, group))
+                .collect(),
+            &edges,
+        )
+    }
+
+    #[test]
+    fn output_starts_with_flowchart_lr() {
+        let mermaid = to_mermaid(&make_graph(vec![], vec![]));
+        assert!(mermaid.starts_with("flowchart LR\n"));
+    }
+
+    #[test]
+    fn mangle_id_produces_only_safe_characters() {
+        let mangled = mangle_id("file:///src/main.rs:10:4:foo");
+        assert!(
+            mangled
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            "mangled id should be alphanumeric/underscore, got: {mangled}"
+        );
+        assert!(mangled.starts_with('n'));
+    }
+
+    #[test]
+    fn mangle_id_keeps_distinct_ids_distinct() {
+        assert_ne!(mangle_id("a:b"), mangle_id("a/b"));
+        assert_ne!(mangle_id("a_3Ab"), mangle_id("a:b"));
+    }
+
+    #[test]
+    fn nodes_are_placed_in_group_subgraph_with_short_label() {
+        let graph = make_graph(vec![("id1", "MyStruct::my_method", "MyStruct")], vec![]);
+        let mermaid = to_mermaid(&graph);
+        assert!(
+            mermaid.contains("  subgraph group_0[\"MyStruct\"]\n    nid1[\"my_method\"]\n  end\n"),
+            "expected node inside its group subgraph, got:\n{mermaid}"
+        );
+    }
+
+    #[test]
+    fn labels_with_quotes_are_escaped() {
+        let graph = make_graph(vec![("id1", "say\"hi\"", "g")], vec![]);
+        let mermaid = to_mermaid(&graph);
+        assert!(
+            mermaid.contains("[\"say#quot;hi#quot;\"]"),
+            "quotes should be escaped, got:\n{mermaid}"
+        );
+    }
+
+    #[test]
+    fn edges_use_mangled_ids_and_show_call_count() {
+        let mut graph = make_graph(
+            vec![("a:1", "A::foo", "A"), ("b:2", "B::bar", "B")],
+            vec![("a:1", "b:2")],
+        );
+        let site = |line| CallSite {
+            file: "src/a.rs".to_string(),
+            line,
+            column: 1,
+        };
+        let plain = to_mermaid(&graph);
+        assert!(
+            plain.contains("  na_3A1 --> nb_3A2\n"),
+            "edge should use mangled ids, got:\n{plain}"
+        );
+
+        graph.edges[0].call_sites = vec![site(1), site(2), site(3)];
+        let counted = to_mermaid(&graph);
+        assert!(
+            counted.contains("  na_3A1 -->|\"3x\"| nb_3A2\n"),
+            "edge should show call count, got:\n{counted}"
+        );
+
+        graph.edges[0].call_sites.clear();
+        graph.edges[0].dispatch = true;
+        let dispatch = to_mermaid(&graph);
+        assert!(
+            dispatch.contains("  na_3A1 -.-> nb_3A2\n"),
+            "dispatch edge should be dotted, got:\n{dispatch}"
+        );
+    }
+
+    #[test]
+    fn truncated_nodes_get_dashed_class() {
+        let mut graph = make_graph(vec![("a", "a", "g"), ("b", "b", "g")], vec![]);
+        assert!(!to_mermaid(&graph).contains("classDef"));
+
+        graph.nodes[1].truncated = true;
+        let mermaid = to_mermaid(&graph);
+        assert!(
+            mermaid.contains("  classDef truncated stroke-dasharray: 5 5\n  class nb truncated\n"),
+            "truncated node should get the dashed class, got:\n{mermaid}"
+        );
+    }
+
+    #[test]
+    fn root_nodes_get_thick_border_class() {
+        let mut graph = make_graph(vec![("a", "main", "g")], vec![]);
+        graph.nodes[0].is_root = true;
+        let mermaid = to_mermaid(&graph);
+        assert!(
+            mermaid.contains("  classDef root stroke-width:4px\n  class na root\n"),
+            "root node should get the root class, got:\n{mermaid}"
+        );
+
+        graph.nodes[0].external = true;
+        let mermaid = to_mermaid(&graph);
+        assert!(
+            mermaid.contains("  classDef external fill:#eee,color:#555\n  class na external\n"),
+            "external node should get the external class, got:\n{mermaid}"
+        );
+    }
+
+    #[test]
+    fn highlighted_nodes_get_class_and_edges_get_link_style() {
+        let mut graph = make_graph(
+            vec![("a", "a", "g"), ("b", "b", "g")],
+            vec![("a", "b"), ("b", "b")],
+        );
+        graph.nodes[1].highlight = Some(Highlight::Cycle);
+        graph.edges[1].highlight = Some(Highlight::Cycle);
+        let mermaid = to_mermaid(&graph);
+        assert!(
+            mermaid.contains("  classDef cycle stroke:red,stroke-width:2px\n  class nb cycle\n"),
+            "highlighted node should get the cycle class, got:\n{mermaid}"
+        );
+        assert!(
+            mermaid.contains("  linkStyle 1 stroke:red,stroke-width:2px\n"),
+            "highlighted edge should be styled by index, got:\n{mermaid}"
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Output format abstraction and registry.
+//!
+//! Each output format implements [`Renderer`] next to its rendering function
+//! (`dot_renderer`, `graph_json`, `mermaid_renderer`) and is listed in [`RENDERERS`].
+//! [`select`] picks one from an explicit `--format` name, or infers it from the output file
+//! extension. Like the individual renderers, this module depends only on `CallGraph`.
+
+use crate::call_graph::CallGraph;
+use crate::dot_renderer::DotRenderer;
+use crate::graph_json::JsonRenderer;
+use crate::mermaid_renderer::MermaidRenderer;
+
+/// Output path that selects standard output instead of a file.
+pub const STDOUT_PATH: &str = "-";
+
+/// Turns a [`CallGraph`] into the text of one output format.
+pub trait Renderer: Sync {
+    /// Format name accepted by `--format`.
+    fn name(&self) -> &'static str;
+    /// File extensions (without the leading `.`) that select this format when `--format`
+    /// is omitted.
+    fn extensions(&self) -> &'static [&'static str];
+    /// Renders the whole graph.
+    fn render(&self, graph: &CallGraph) -> String;
+}
+
+/// Every available output format. The first entry is the default.
+pub static RENDERERS: &[&dyn Renderer] = &[&DotRenderer, &JsonRenderer, &MermaidRenderer];
+
+/// Chooses the renderer for an output.
+///
+/// An explicit `format` must name a registered renderer. Otherwise the extension of
+/// `output_path` decides, falling back to the default (DOT) for unknown extensions and for
+/// [`STDOUT_PATH`].
+pub fn select(format: Option<&str>, output_path: &str) -> anyhow::Result<&'static dyn Renderer> {
+    if let Some(name) = format {
+        return RENDERERS
+            .iter()
+            .copied()
+            .find(|r| r.name() == name)
+            .ok_or_else(|| {
+                let known: Vec<&str> = RENDERERS.iter().map(|r| r.name()).collect();
+                anyhow::anyhow!(
+                    "unknown output format '{}' (available: {})",
+                    name,
+                    known.join(", ")
+                )
+            });
+    }
+
+    let extension = std::path::Path::new(output_path)
+        .extension()
+        .and_then(|e| e.to_str());
+    let inferred = extension.and_then(|ext| {
+        RENDERERS
+            .iter()
+            .copied()
+            .find(|r| r.extensions().contains(&ext))
+    });
+    Ok(inferred.unwrap_or(RENDERERS[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_format_wins_over_extension() {
+        let renderer = select(Some("mermaid"), "out.json").unwrap();
+        assert_eq!(renderer.name(), "mermaid");
+    }
+
+    #[test]
+    fn unknown_format_lists_available_formats() {
+        let err = select(Some("svg"), "out.dot").err().unwrap().to_string();
+        assert!(err.contains("svg"), "error should name the format: {err}");
+        assert!(
+            err.contains("dot, json, mermaid"),
+            "error should list formats: {err}"
+        );
+    }
+
+    #[test]
+    fn format_is_inferred_from_extension() {
+        assert_eq!(select(None, "tmp/graph.json").unwrap().name(), "json");
+        assert_eq!(select(None, "docs/graph.mmd").unwrap().name(), "mermaid");
+        assert_eq!(select(None, "graph.gv").unwrap().name(), "dot");
+    }
+
+    #[test]
+    fn unknown_extension_and_stdout_default_to_dot() {
+        assert_eq!(select(None, "graph.txt").unwrap().name(), "dot");
+        assert_eq!(select(None, STDOUT_PATH).unwrap().name(), "dot");
+    }
+}